@@ -0,0 +1,205 @@
+use crate::constants::*;
+use crate::events::*;
+use crate::settings::Settings;
+use crate::GameState;
+use bevy::{
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+
+#[derive(Resource)]
+pub struct MasterVolume(pub f32);
+
+#[derive(Resource)]
+pub struct MusicVolume(pub f32);
+
+#[derive(Resource)]
+pub struct SfxVolume(pub f32);
+
+impl Default for MasterVolume {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl Default for MusicVolume {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl Default for SfxVolume {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+#[derive(Resource)]
+pub struct SoundEffects {
+    pub shot: Handle<AudioSource>,
+    pub explosion: Handle<AudioSource>,
+    pub graze: Handle<AudioSource>,
+    pub hit: Handle<AudioSource>,
+    pub bomb: Handle<AudioSource>,
+    pub hover: Handle<AudioSource>,
+    pub press: Handle<AudioSource>,
+}
+
+#[derive(Component)]
+pub struct Music {
+    pub track: &'static str,
+    pub fade: f32,
+    pub fading_out: bool,
+}
+
+pub fn load_sound_effects(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects {
+        shot: asset_server.load(SFX_SHOT),
+        explosion: asset_server.load(SFX_EXPLOSION),
+        graze: asset_server.load(SFX_GRAZE),
+        hit: asset_server.load(SFX_HIT),
+        bomb: asset_server.load(SFX_BOMB),
+        hover: asset_server.load(SFX_HOVER),
+        press: asset_server.load(SFX_PRESS),
+    });
+}
+
+pub fn apply_volume_settings(
+    settings: Res<Settings>,
+    mut master: ResMut<MasterVolume>,
+    mut music: ResMut<MusicVolume>,
+    mut sfx: ResMut<SfxVolume>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    master.0 = settings.master_volume;
+    music.0 = settings.music_volume;
+    sfx.0 = settings.sfx_volume;
+}
+
+fn play_sfx(commands: &mut Commands, source: &Handle<AudioSource>, volume: f32) {
+    if volume <= 0. {
+        return;
+    }
+    commands.spawn(AudioBundle {
+        source: source.clone(),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new_relative(volume)),
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn play_gameplay_sfx(
+    mut commands: Commands,
+    sounds: Res<SoundEffects>,
+    master: Res<MasterVolume>,
+    sfx: Res<SfxVolume>,
+    mut shots: EventReader<ShotFired>,
+    mut destroyed: EventReader<EnemyDestroyed>,
+    mut grazes: EventReader<Graze>,
+    mut player_hits: EventReader<PlayerHit>,
+    mut bombs: EventReader<BombUsed>,
+) {
+    let volume = master.0 * sfx.0;
+    // Several copies of the same event in one frame only play the sound once.
+    let cues = [
+        (shots.read().count(), &sounds.shot),
+        (destroyed.read().count(), &sounds.explosion),
+        (grazes.read().count(), &sounds.graze),
+        (player_hits.read().count(), &sounds.hit),
+        (bombs.read().count(), &sounds.bomb),
+    ];
+    for (count, source) in cues {
+        if count > 0 {
+            play_sfx(&mut commands, source, volume);
+        }
+    }
+}
+
+pub fn play_menu_sfx(
+    mut commands: Commands,
+    sounds: Res<SoundEffects>,
+    master: Res<MasterVolume>,
+    sfx: Res<SfxVolume>,
+    interaction_query: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+) {
+    let volume = master.0 * sfx.0;
+    for interaction in interaction_query.iter() {
+        match *interaction {
+            Interaction::Pressed => play_sfx(&mut commands, &sounds.press, volume),
+            Interaction::Hovered => play_sfx(&mut commands, &sounds.hover, volume),
+            Interaction::None => {}
+        }
+    }
+}
+
+fn music_track(state: &GameState) -> &'static str {
+    match state {
+        GameState::Running | GameState::Restarting | GameState::Demo => MUSIC_STAGE,
+        GameState::GameOver => MUSIC_GAME_OVER,
+        _ => MUSIC_MENU,
+    }
+}
+
+pub fn change_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    asset_server: Res<AssetServer>,
+    mut music_query: Query<&mut Music>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let track = music_track(state.get());
+    let mut playing = false;
+    for mut music in music_query.iter_mut() {
+        if music.track == track {
+            music.fading_out = false;
+            playing = true;
+        } else {
+            music.fading_out = true;
+        }
+    }
+    if playing {
+        return;
+    }
+    commands.spawn((
+        AudioBundle {
+            source: asset_server.load(track),
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Loop,
+                volume: Volume::new_relative(0.),
+                ..default()
+            },
+        },
+        Music {
+            track,
+            fade: 0.,
+            fading_out: false,
+        },
+    ));
+}
+
+pub fn fade_music(
+    mut commands: Commands,
+    time: Res<Time>,
+    master: Res<MasterVolume>,
+    volume: Res<MusicVolume>,
+    mut music_query: Query<(Entity, &mut Music, Option<&AudioSink>)>,
+) {
+    let step = time.delta_seconds() / MUSIC_CROSSFADE;
+    for (entity, mut music, sink) in music_query.iter_mut() {
+        if music.fading_out {
+            music.fade = (music.fade - step).max(0.);
+            if music.fade == 0. {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        } else {
+            music.fade = (music.fade + step).min(1.);
+        }
+        if let Some(sink) = sink {
+            sink.set_volume(music.fade * master.0 * volume.0);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::constants::*;
+use crate::playfield::Playfield;
+use crate::stage::{StageDefinition, StageDirector};
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct BackgroundLayer {
+    pub parallax: f32,
+}
+
+#[derive(Resource)]
+pub struct BackgroundScroll {
+    pub speed: f32,
+    pub target: f32,
+    pub rate: f32,
+}
+
+impl Default for BackgroundScroll {
+    fn default() -> Self {
+        Self {
+            speed: BACKGROUND_SCROLL_SPEED,
+            target: BACKGROUND_SCROLL_SPEED,
+            rate: 0.,
+        }
+    }
+}
+
+impl BackgroundScroll {
+    pub fn set(&mut self, speed: f32) {
+        *self = Self {
+            speed,
+            target: speed,
+            rate: 0.,
+        };
+    }
+
+    pub fn change_to(&mut self, target: f32, duration: f32) {
+        if duration <= 0. {
+            self.set(target);
+            return;
+        }
+        self.target = target;
+        self.rate = (target - self.speed).abs() / duration;
+    }
+}
+
+pub fn setup_background(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    playfield: Res<Playfield>,
+) {
+    let texture = asset_server.load("bg6.png");
+    let tile = Vec2::splat(BACKGROUND_TILE_SIZE);
+    let columns = (playfield.rect.width() / tile.x).ceil() as i32;
+    let rows = (playfield.rect.height() / tile.y).ceil() as i32 + 1;
+
+    for (index, (parallax, color)) in BACKGROUND_LAYERS.iter().enumerate() {
+        for row in 0..rows {
+            for column in 0..columns {
+                let position = Vec2::new(
+                    playfield.rect.min.x + (column as f32 + 0.5) * tile.x,
+                    playfield.rect.min.y + (row as f32 + 0.5) * tile.y,
+                );
+                commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: *color,
+                            custom_size: Some(tile),
+                            flip_x: index % 2 == 1,
+                            flip_y: index % 2 == 1,
+                            ..default()
+                        },
+                        texture: texture.clone(),
+                        transform: Transform::from_translation(
+                            position.extend(BACKGROUND_Z + index as f32 * 0.1),
+                        ),
+                        ..default()
+                    },
+                    BackgroundLayer {
+                        parallax: *parallax,
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub fn reset_background(
+    mut scroll: ResMut<BackgroundScroll>,
+    director: Res<StageDirector>,
+    stages: Res<Assets<StageDefinition>>,
+) {
+    scroll.set(
+        stages
+            .get(&director.stage)
+            .map_or(BACKGROUND_SCROLL_SPEED, |stage| stage.scroll_speed),
+    );
+}
+
+pub fn scroll_background(
+    time: Res<Time>,
+    playfield: Res<Playfield>,
+    mut scroll: ResMut<BackgroundScroll>,
+    mut query: Query<(&mut Transform, &BackgroundLayer)>,
+) {
+    if scroll.speed != scroll.target {
+        let step = scroll.rate * time.delta_seconds();
+        scroll.speed = if scroll.speed < scroll.target {
+            (scroll.speed + step).min(scroll.target)
+        } else {
+            (scroll.speed - step).max(scroll.target)
+        };
+    }
+
+    let rows = (playfield.rect.height() / BACKGROUND_TILE_SIZE).ceil() + 1.;
+    let wrap = rows * BACKGROUND_TILE_SIZE;
+    for (mut transform, layer) in query.iter_mut() {
+        transform.translation.y -= scroll.speed * layer.parallax * time.delta_seconds();
+        if transform.translation.y < playfield.bottom() - BACKGROUND_TILE_SIZE / 2. {
+            transform.translation.y += wrap;
+        }
+    }
+}
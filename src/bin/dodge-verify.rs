@@ -0,0 +1,329 @@
+use bevy::prelude::*;
+use bevy_dodge::headless::{headless_app, load_definitions};
+use bevy_dodge::replay::*;
+use bevy_dodge::ships::{SelectedShip, ShipDefinition, ShipDefinitions};
+use bevy_dodge::{Difficulty, GameMode, GameState, GameplaySet, Score};
+use std::fmt;
+use std::process::ExitCode;
+
+#[derive(Debug)]
+enum Failure {
+    Unreadable(String),
+    NotRanked(&'static str),
+    AssetsNotLoaded,
+    UnknownShip(String),
+    HashMismatch {
+        tick: usize,
+    },
+    EndedEarly {
+        tick: usize,
+        length: usize,
+    },
+    OutlivedReplay {
+        length: usize,
+    },
+    HashCount {
+        tick: usize,
+        claimed: usize,
+        simulated: usize,
+    },
+    ScoreMismatch {
+        claimed: i32,
+        simulated: i32,
+    },
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Unreadable(err) => write!(f, "could not read replay: {}", err),
+            Failure::NotRanked(reason) => write!(f, "replay is not a ranked run: {}", reason),
+            Failure::AssetsNotLoaded => write!(f, "ship and stage definitions did not load"),
+            Failure::UnknownShip(ship) => write!(f, "replay uses unknown ship '{}'", ship),
+            Failure::HashMismatch { tick } => {
+                write!(f, "diverged at tick {}: state hash mismatch", tick)
+            }
+            Failure::EndedEarly { tick, length } => write!(
+                f,
+                "diverged at tick {}: run ended but the replay has {} ticks",
+                tick, length
+            ),
+            Failure::OutlivedReplay { length } => write!(
+                f,
+                "diverged at tick {}: run was still going when the replay ended",
+                length
+            ),
+            Failure::HashCount {
+                tick,
+                claimed,
+                simulated,
+            } => write!(
+                f,
+                "diverged at tick {}: replay has {} state hashes but the run made {}",
+                tick, claimed, simulated
+            ),
+            Failure::ScoreMismatch { claimed, simulated } => write!(
+                f,
+                "replay claims a score of {} but the run scored {}",
+                claimed, simulated
+            ),
+        }
+    }
+}
+
+fn verifier_app() -> App {
+    let mut app = headless_app();
+    app.add_systems(
+        FixedUpdate,
+        (play_replay_input, record_input)
+            .chain()
+            .run_if(in_state(GameState::Running))
+            .in_set(GameplaySet::Input),
+    )
+    .add_systems(
+        OnEnter(GameState::Running),
+        use_replay_hash_interval.after(start_recording),
+    );
+    app
+}
+
+fn use_replay_hash_interval(playback: Res<ReplayPlayback>, mut recorder: ResMut<ReplayRecorder>) {
+    recorder.replay.hash_interval = playback.replay.hash_interval;
+}
+
+// Only normal runs without cheats or assists count; anything else is turned
+// away before simulating.
+fn check_ranked(replay: &Replay) -> Result<(), Failure> {
+    if replay.practice {
+        return Err(Failure::NotRanked("recorded in practice mode"));
+    }
+    if replay.cheated {
+        return Err(Failure::NotRanked("cheats were used"));
+    }
+    if replay.assisted {
+        return Err(Failure::NotRanked("assists were used"));
+    }
+    Ok(())
+}
+
+fn start(app: &mut App, replay: &Replay) -> Result<(), Failure> {
+    if !load_definitions(app) {
+        return Err(Failure::AssetsNotLoaded);
+    }
+
+    let ship = app
+        .world
+        .resource::<ShipDefinitions>()
+        .handles
+        .iter()
+        .filter_map(|handle| app.world.resource::<Assets<ShipDefinition>>().get(handle))
+        .find(|definition| definition.id == replay.ship)
+        .cloned()
+        .ok_or_else(|| Failure::UnknownShip(replay.ship.clone()))?;
+
+    app.world.resource_mut::<SelectedShip>().definition = ship;
+    *app.world.resource_mut::<Difficulty>() = replay.difficulty;
+    *app.world.resource_mut::<GameMode>() = GameMode::Normal;
+    app.world
+        .insert_resource(ReplayPlayback::new(replay.clone()));
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Running);
+    Ok(())
+}
+
+fn verify(replay: &Replay) -> Result<usize, Failure> {
+    check_ranked(replay)?;
+    let mut app = verifier_app();
+    start(&mut app, replay)?;
+
+    let length = replay.inputs.len();
+    let mut checked = 0;
+    loop {
+        app.update();
+
+        let simulated = &app.world.resource::<ReplayRecorder>().replay;
+        if simulated.hashes.len() != checked {
+            checked = simulated.hashes.len();
+            if let Some(tick) = simulated.first_divergence(replay) {
+                return Err(Failure::HashMismatch { tick });
+            }
+        }
+
+        let tick = app.world.resource::<ReplayPlayback>().tick;
+        if *app.world.resource::<State<GameState>>().get() == GameState::GameOver {
+            if tick < length {
+                return Err(Failure::EndedEarly { tick, length });
+            }
+            if replay.hashes.len() != checked {
+                return Err(Failure::HashCount {
+                    tick: (replay.hashes.len().min(checked) + 1) * replay.hash_interval as usize,
+                    claimed: replay.hashes.len(),
+                    simulated: checked,
+                });
+            }
+            let score = app.world.resource::<Score>().value;
+            if score != replay.score {
+                return Err(Failure::ScoreMismatch {
+                    claimed: replay.score,
+                    simulated: score,
+                });
+            }
+            return Ok(checked);
+        }
+        if tick > length {
+            return Err(Failure::OutlivedReplay { length });
+        }
+    }
+}
+
+fn load(path: &str) -> Result<Replay, Failure> {
+    let bytes = std::fs::read(path).map_err(|err| Failure::Unreadable(err.to_string()))?;
+    Replay::from_bytes(&bytes).map_err(|err| Failure::Unreadable(err.to_string()))
+}
+
+fn main() -> ExitCode {
+    let Some(path) = std::env::args().nth(1) else {
+        eprintln!("usage: dodge-verify <replay>");
+        return ExitCode::from(2);
+    };
+
+    let result = load(&path).and_then(|replay| {
+        if replay.game_version != env!("CARGO_PKG_VERSION") {
+            eprintln!(
+                "warning: replay was recorded with version '{}', this is {}",
+                replay.game_version,
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+        verify(&replay).map(|hashes| (replay, hashes))
+    });
+    match result {
+        Ok((replay, hashes)) => {
+            println!(
+                "PASS: {} scored {} on {:?} with {} over {} ticks ({} state hashes matched)",
+                if replay.name.is_empty() {
+                    "anonymous"
+                } else {
+                    &replay.name
+                },
+                replay.score,
+                replay.difficulty,
+                replay.ship,
+                replay.inputs.len(),
+                hashes
+            );
+            ExitCode::SUCCESS
+        }
+        Err(failure) => {
+            println!("FAIL: {}", failure);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::OnceLock;
+
+    // A run left alone sits still until it is shot down.
+    const MAX_RECORDED_TICKS: usize = 64 * 60 * 10;
+
+    fn recorded_run() -> Replay {
+        static RUN: OnceLock<Replay> = OnceLock::new();
+        RUN.get_or_init(|| {
+            let mut app = headless_app();
+            app.add_systems(
+                FixedUpdate,
+                record_input
+                    .run_if(in_state(GameState::Running))
+                    .in_set(GameplaySet::Input),
+            );
+            assert!(load_definitions(&mut app));
+            app.world
+                .resource_mut::<NextState<GameState>>()
+                .set(GameState::Running);
+            while *app.world.resource::<State<GameState>>().get() != GameState::GameOver {
+                assert!(
+                    app.world.resource::<ReplayRecorder>().replay.inputs.len() < MAX_RECORDED_TICKS
+                );
+                app.update();
+            }
+            let mut replay = app.world.resource::<ReplayRecorder>().replay.clone();
+            replay.score = app.world.resource::<Score>().value;
+            replay
+        })
+        .clone()
+    }
+
+    #[test]
+    fn accepts_a_recorded_run() {
+        let replay = recorded_run();
+        assert!(!replay.hashes.is_empty());
+        let replay = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(verify(&replay).unwrap(), replay.hashes.len());
+    }
+
+    #[test]
+    fn rejects_a_tampered_score() {
+        let mut replay = recorded_run();
+        replay.score += 1;
+        assert!(matches!(
+            verify(&replay),
+            Err(Failure::ScoreMismatch { claimed, simulated })
+                if claimed == simulated + 1
+        ));
+    }
+
+    #[test]
+    fn rejects_a_tampered_hash() {
+        let mut replay = recorded_run();
+        replay.hashes[0] ^= 1;
+        assert!(matches!(
+            verify(&replay),
+            Err(Failure::HashMismatch { tick }) if tick == replay.hash_interval as usize
+        ));
+    }
+
+    #[test]
+    fn rejects_a_missing_hash() {
+        let mut replay = recorded_run();
+        replay.hashes.pop();
+        assert!(matches!(verify(&replay), Err(Failure::HashCount { .. })));
+    }
+
+    #[test]
+    fn rejects_runs_that_end_early_or_late() {
+        let mut longer = recorded_run();
+        longer.inputs.push(0);
+        assert!(matches!(verify(&longer), Err(Failure::EndedEarly { .. })));
+
+        let mut shorter = recorded_run();
+        shorter.inputs.pop();
+        assert!(matches!(
+            verify(&shorter),
+            Err(Failure::OutlivedReplay { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_unranked_runs() {
+        for replay in [
+            Replay {
+                practice: true,
+                ..default()
+            },
+            Replay {
+                cheated: true,
+                ..default()
+            },
+            Replay {
+                assisted: true,
+                ..default()
+            },
+        ] {
+            assert!(matches!(verify(&replay), Err(Failure::NotRanked(_))));
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::*;
+use crate::stats::RunStats;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Deserialize, Resource, Serialize)]
+pub struct Combo {
+    pub count: u32,
+    pub timer: f32,
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> i32 {
+        (1 + self.count / COMBO_STEP).min(COMBO_MAX_MULTIPLIER) as i32
+    }
+
+    fn extend(&mut self, hits: u32) {
+        if hits == 0 {
+            return;
+        }
+        self.count += hits;
+        self.timer = COMBO_TIMEOUT;
+    }
+}
+
+pub fn reset_combo(mut combo: ResMut<Combo>) {
+    *combo = Combo::default();
+}
+
+pub fn update_combo(
+    time: Res<Time>,
+    mut combo: ResMut<Combo>,
+    mut stats: ResMut<RunStats>,
+    mut kills: EventReader<EnemyDestroyed>,
+    mut grazes: EventReader<Graze>,
+    mut player_hit: EventReader<PlayerHit>,
+) {
+    if player_hit.read().count() > 0 {
+        *combo = Combo::default();
+        return;
+    }
+
+    combo.timer -= time.delta_seconds();
+    if combo.timer <= 0. {
+        combo.count = 0;
+        combo.timer = 0.;
+    }
+
+    let hits = kills.read().count() + grazes.read().count();
+    combo.extend(hits as u32);
+    stats.max_combo = stats.max_combo.max(combo.count);
+}
+
+pub fn update_combo_hud(
+    combo: Res<Combo>,
+    mut text_query: Query<&mut Text, With<ComboText>>,
+    mut bar_query: Query<&mut Sprite, With<ComboBar>>,
+) {
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = if combo.count > 0 {
+            format!("x{} ({})", combo.multiplier(), combo.count)
+        } else {
+            String::new()
+        };
+    }
+    for mut sprite in bar_query.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(combo.timer / COMBO_TIMEOUT * COMBO_BAR_WIDTH, 3.));
+    }
+}
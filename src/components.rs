@@ -1,4 +1,7 @@
+use crate::practice::PracticeOption;
+use crate::settings::SettingsOption;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Component)]
 pub struct MainCamera;
@@ -9,9 +12,34 @@ pub struct Player;
 #[derive(Component)]
 pub struct Ship;
 
-#[derive(Component)]
+#[derive(Clone, Component, Deserialize, Serialize)]
 pub struct Enemy {
     pub speed: f32,
+    pub health: i32,
+}
+
+#[derive(Component)]
+pub struct Grazed;
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct Invulnerable {
+    pub timer: f32,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ItemKind {
+    Point,
+    Power,
+    BombFragment,
+    LifeFragment,
+}
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub velocity: Vec2,
+    pub magnetized: bool,
+    pub auto_collected: bool,
 }
 
 #[derive(Component)]
@@ -21,4 +49,51 @@ pub struct ColorText;
 pub struct ScoreText;
 
 #[derive(Component)]
-pub struct Bullet;
+pub struct ComboText;
+
+#[derive(Component)]
+pub struct ComboBar;
+
+#[derive(Component)]
+pub struct StockText;
+
+#[derive(Component)]
+pub struct Hud;
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct Bullet {
+    pub velocity: Vec2,
+    pub damage: i32,
+}
+
+#[derive(Clone, Copy, Component, PartialEq)]
+pub enum MenuButton {
+    Play,
+    Difficulty,
+    GameSpeed,
+    HighScores,
+    SelectShip(usize),
+    Back,
+    Retry,
+    MainMenu,
+    SaveReplay,
+    Resume,
+    Restart,
+    Settings,
+    Setting(SettingsOption),
+    CloseSettings,
+    Practice,
+    PracticeOption(PracticeOption),
+    StartPractice,
+    HighScoreMode,
+    HighScoreDifficulty,
+}
+
+#[derive(Component)]
+pub struct NameEntryText;
+
+#[derive(Component)]
+pub struct HighScoreTitle;
+
+#[derive(Component)]
+pub struct HighScoreLine(pub usize);
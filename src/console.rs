@@ -0,0 +1,407 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::enemies::{spawn_enemy_archetype, EnemyArchetype};
+use crate::events::DamageEnemy;
+use crate::lasers::{CurvyLaser, EnemyLaser};
+use crate::player::GodMode;
+use crate::speed::GameSpeed;
+use crate::stage::{StageDefinition, StageDefinitions, StageDirector};
+use crate::{RunFlags, RunSeed, Score};
+use bevy::{ecs::system::CommandQueue, prelude::*};
+use bevy_prng::ChaCha8Rng;
+use bevy_rand::prelude::*;
+use rand_core::SeedableRng;
+use std::collections::BTreeMap;
+
+pub type ConsoleResult = Result<String, String>;
+
+#[derive(Clone, Copy)]
+pub struct ConsoleCommand {
+    pub usage: &'static str,
+    pub cheat: bool,
+    pub handler: fn(&mut World, &[&str]) -> ConsoleResult,
+}
+
+#[derive(Default, Resource)]
+pub struct ConsoleCommands {
+    pub commands: BTreeMap<&'static str, ConsoleCommand>,
+}
+
+#[derive(Default, Resource)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub history: Vec<String>,
+    pub pending: Vec<String>,
+    // Enemies marked by `kill`, finished off on the first tick after the
+    // console closes.
+    pub kills: Vec<Entity>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("console: {}", line);
+        self.history.push(line);
+        let overflow = self.history.len().saturating_sub(CONSOLE_HISTORY);
+        self.history.drain(..overflow);
+    }
+}
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+pub trait AddConsoleCommand {
+    fn add_console_command(&mut self, name: &'static str, command: ConsoleCommand) -> &mut Self;
+}
+
+impl AddConsoleCommand for App {
+    fn add_console_command(&mut self, name: &'static str, command: ConsoleCommand) -> &mut Self {
+        self.init_resource::<ConsoleCommands>();
+        self.world
+            .resource_mut::<ConsoleCommands>()
+            .commands
+            .insert(name, command);
+        self
+    }
+}
+
+pub fn console_closed(console: Res<Console>) -> bool {
+    !console.open
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_console_command(
+                "help",
+                ConsoleCommand {
+                    usage: "help",
+                    cheat: false,
+                    handler: help,
+                },
+            )
+            .add_console_command(
+                "spawn",
+                ConsoleCommand {
+                    usage: "spawn <archetype> <x> <y>",
+                    cheat: true,
+                    handler: spawn,
+                },
+            )
+            .add_console_command(
+                "god",
+                ConsoleCommand {
+                    usage: "god on|off",
+                    cheat: true,
+                    handler: god,
+                },
+            )
+            .add_console_command(
+                "set",
+                ConsoleCommand {
+                    usage: "set score <n>",
+                    cheat: true,
+                    handler: set,
+                },
+            )
+            .add_console_command(
+                "seed",
+                ConsoleCommand {
+                    usage: "seed <n>",
+                    cheat: true,
+                    handler: seed,
+                },
+            )
+            .add_console_command(
+                "stage",
+                ConsoleCommand {
+                    usage: "stage <name> <time>",
+                    cheat: true,
+                    handler: stage,
+                },
+            )
+            .add_console_command(
+                "timescale",
+                ConsoleCommand {
+                    usage: "timescale <factor>",
+                    cheat: true,
+                    handler: timescale,
+                },
+            )
+            .add_console_command(
+                "kill",
+                ConsoleCommand {
+                    usage: "kill all",
+                    cheat: true,
+                    handler: kill,
+                },
+            )
+            .add_systems(Startup, setup_console)
+            .add_systems(
+                Update,
+                (
+                    toggle_console,
+                    console_input,
+                    execute_console_commands,
+                    update_console,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn setup_console(mut commands: Commands) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(4.)),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.85).into(),
+            z_index: ZIndex::Global(200),
+            visibility: Visibility::Hidden,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 12.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                        ..default()
+                    },
+                ),
+                ConsoleText,
+            ));
+        });
+}
+
+fn toggle_console(keyboard_input: Res<Input<KeyCode>>, mut console: ResMut<Console>) {
+    if keyboard_input.just_pressed(KeyCode::Grave) {
+        console.open = !console.open;
+        console.input.clear();
+    }
+}
+
+fn console_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut console: ResMut<Console>,
+) {
+    if !console.open {
+        characters.clear();
+        return;
+    }
+    for event in characters.read() {
+        if !event.char.is_control() && event.char != '`' {
+            console.input.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let line = std::mem::take(&mut console.input);
+        if !line.trim().is_empty() {
+            console.pending.push(line);
+        }
+    }
+}
+
+fn execute_console_commands(world: &mut World) {
+    if world.resource::<Console>().pending.is_empty() {
+        return;
+    }
+    let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in pending {
+        world.resource_mut::<Console>().print(format!("> {}", line));
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let command = world
+            .resource::<ConsoleCommands>()
+            .commands
+            .get(words[0])
+            .copied();
+        let output = match command {
+            Some(command) => {
+                let result = (command.handler)(world, &words[1..]);
+                if command.cheat && result.is_ok() {
+                    world.resource_mut::<RunFlags>().cheated = true;
+                }
+                result.unwrap_or_else(|err| format!("error: {}\nusage: {}", err, command.usage))
+            }
+            None => format!("unknown command '{}', try 'help'", words[0]),
+        };
+        let mut console = world.resource_mut::<Console>();
+        for line in output.lines() {
+            console.print(line);
+        }
+    }
+}
+
+fn update_console(
+    console: Res<Console>,
+    mut node_query: Query<&mut Visibility, (With<Node>, Without<ConsoleText>)>,
+    mut text_query: Query<(&Parent, &mut Text), With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for (parent, mut text) in text_query.iter_mut() {
+        let mut lines = console.history.clone();
+        lines.push(format!("> {}_", console.input));
+        text.sections[0].value = lines.join("\n");
+        if let Ok(mut visibility) = node_query.get_mut(parent.get()) {
+            *visibility = if console.open {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: Option<&&str>, name: &str) -> Result<T, String> {
+    value
+        .ok_or_else(|| format!("missing {}", name))?
+        .parse()
+        .map_err(|_| format!("invalid {}", name))
+}
+
+fn help(world: &mut World, _: &[&str]) -> ConsoleResult {
+    Ok(world
+        .resource::<ConsoleCommands>()
+        .commands
+        .values()
+        .map(|command| command.usage)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> ConsoleResult {
+    let name = args.first().ok_or("missing archetype")?;
+    let archetype =
+        EnemyArchetype::from_name(name).ok_or_else(|| format!("unknown archetype '{}'", name))?;
+    let position = Vec2::new(parse(args.get(1), "x")?, parse(args.get(2), "y")?);
+
+    let asset_server = world.resource::<AssetServer>().clone();
+    let speed_scale = world.resource::<crate::Difficulty>().enemy_speed_scale();
+    let mut queue = CommandQueue::default();
+    let mut commands = Commands::new(&mut queue, world);
+    spawn_enemy_archetype(
+        &mut commands,
+        &asset_server,
+        archetype,
+        position,
+        speed_scale,
+        Vec::new(),
+    );
+    queue.apply(world);
+    Ok(format!(
+        "spawned {:?} at {}, {}",
+        archetype, position.x, position.y
+    ))
+}
+
+fn god(world: &mut World, args: &[&str]) -> ConsoleResult {
+    let enabled = match args.first() {
+        Some(&"on") => true,
+        Some(&"off") => false,
+        _ => return Err("expected on or off".to_string()),
+    };
+    world.resource_mut::<GodMode>().enabled = enabled;
+    Ok(format!("god mode {}", args[0]))
+}
+
+fn set(world: &mut World, args: &[&str]) -> ConsoleResult {
+    match args.first() {
+        Some(&"score") => {
+            let value = parse(args.get(1), "score")?;
+            world.resource_mut::<Score>().value = value;
+            Ok(format!("score set to {}", value))
+        }
+        _ => Err("unknown variable".to_string()),
+    }
+}
+
+fn seed(world: &mut World, args: &[&str]) -> ConsoleResult {
+    let value = parse(args.first(), "seed")?;
+    world.resource_mut::<RunSeed>().value = value;
+    *world.resource_mut::<GlobalEntropy<ChaCha8Rng>>() =
+        GlobalEntropy::<ChaCha8Rng>::seed_from_u64(value);
+    Ok(format!("rng reseeded with {}", value))
+}
+
+fn stage(world: &mut World, args: &[&str]) -> ConsoleResult {
+    let name = args.first().ok_or("missing stage name")?;
+    let time: f32 = match args.get(1) {
+        Some(_) => parse(args.get(1), "time")?,
+        None => 0.,
+    };
+    let stages = world.resource::<Assets<StageDefinition>>();
+    let handle = world
+        .resource::<StageDefinitions>()
+        .handles
+        .iter()
+        .find(|handle| stages.get(*handle).is_some_and(|stage| stage.name == *name))
+        .cloned()
+        .ok_or_else(|| format!("unknown stage '{}'", name))?;
+    // Seeking replays the timeline from the start, so whatever is on screen
+    // now would otherwise be spawned a second time.
+    let hazards: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<Enemy>, With<EnemyLaser>, With<CurvyLaser>)>>()
+        .iter(world)
+        .collect();
+    for entity in hazards {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    let mut director = world.resource_mut::<StageDirector>();
+    director.stage = handle;
+    director.seek(time);
+    Ok(format!("jumped to {} at {}s", name, time))
+}
+
+fn timescale(world: &mut World, args: &[&str]) -> ConsoleResult {
+    let factor: f32 = parse(args.first(), "factor")?;
+    if factor <= 0. {
+        return Err("factor must be positive".to_string());
+    }
+    world.resource_mut::<GameSpeed>().factor = factor;
+    Ok(format!("time scale set to {}", factor))
+}
+
+fn kill(world: &mut World, args: &[&str]) -> ConsoleResult {
+    if args.first() != Some(&"all") {
+        return Err("expected 'all'".to_string());
+    }
+    // Gameplay is paused while the console is open, so any event sent now
+    // would expire before it was read. The kills are queued instead and go
+    // through the normal damage path once gameplay resumes.
+    let enemies: Vec<Entity> = world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(world)
+        .collect();
+    let count = enemies.len();
+    world.resource_mut::<Console>().kills.extend(enemies);
+    Ok(format!("killing {} enemies when the console closes", count))
+}
+
+pub fn apply_console_kills(
+    mut console: ResMut<Console>,
+    enemy_query: Query<&Enemy>,
+    mut damage_enemy: EventWriter<DamageEnemy>,
+) {
+    for target in console.kills.drain(..) {
+        if let Ok(enemy) = enemy_query.get(target) {
+            damage_enemy.send(DamageEnemy {
+                target,
+                amount: enemy.health,
+            });
+        }
+    }
+}
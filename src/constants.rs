@@ -11,6 +11,27 @@ pub const ENEMY_SPEED: f32 = 200.;
 pub const FAST_SPEED: f32 = 150.;
 pub const SLOW_SPEED: f32 = 50.;
 pub const MAX_ENEMIES: usize = 50;
+pub const ENEMY_SPAWN_OFFSET: f32 = 20.;
+pub const ENEMY_HEALTH: i32 = 2;
+pub const TURRET_COLOR: Color = Color::PURPLE;
+pub const TURRET_HEALTH: i32 = 8;
+pub const TURRET_HOVER_Y: f32 = 120.;
+pub const TURRET_HOVER_TIME: f32 = 6.;
+pub const BOSS_COLOR: Color = Color::CRIMSON;
+pub const BOSS_HEALTH: i32 = 300;
+pub const BOSS_HOVER_Y: f32 = 150.;
+pub const BOSS_SIZE: f32 = 48.;
+
+pub const LASER_WARNING_COLOR: Color = Color::rgba(1., 0.3, 0.3, 0.5);
+pub const LASER_WARNING_WIDTH: f32 = 1.;
+pub const LASER_ACTIVE_COLOR: Color = Color::rgba(1., 0.4, 0.6, 0.9);
+pub const CURVY_LASER_COLOR: Color = Color::rgba(0.7, 0.5, 1., 0.9);
+
+pub const ENEMY_HITBOX: Vec2 = Vec2 { x: 7., y: 7. };
+pub const ENEMY_SHOT_HITBOX: Vec2 = Vec2 { x: 20., y: 20. };
+pub const BULLET_HITBOX: Vec2 = Vec2 { x: 2., y: 2. };
+
+pub const STAGE_DEFINITIONS: &[&str] = &["stages/stage1.stage.ron"];
 
 pub const PLAYER_SIZE: Vec3 = Vec3 {
     x: 5.,
@@ -21,7 +42,134 @@ pub const PLAYER_FOCUS_SPEED: f32 = 50.;
 pub const PLAYER_SPEED: f32 = 150.;
 pub const PLAYER_COLOR: Color = Color::GREEN;
 
+pub const PLAYER_FIRE_RATE: f32 = 12.;
+pub const BULLET_SPEED: f32 = 300.;
+pub const SHOT_DAMAGE: i32 = 1;
+pub const FOCUSED_SHOT_DAMAGE: i32 = 2;
+pub const SPREAD_ANGLE: f32 = 8.;
+pub const FOCUSED_SPREAD_ANGLE: f32 = 2.;
+pub const NEEDLE_SPACING: f32 = 10.;
+pub const FOCUSED_NEEDLE_SPACING: f32 = 4.;
+pub const POWER_PER_LEVEL: u32 = 25;
+pub const BOMB_DAMAGE: i32 = 1000;
+
+pub const MISSILE_SIZE: Vec2 = Vec2 { x: 4., y: 8. };
+pub const MISSILE_COLOR: Color = Color::SALMON;
+pub const MISSILE_SPEED: f32 = 220.;
+pub const MISSILE_TURN_RATE: f32 = 4.;
+pub const MISSILE_LIFETIME: f32 = 3.;
+pub const MISSILE_DAMAGE: i32 = 2;
+
+pub const LASER_COLOR: Color = Color::rgba(0.6, 0.9, 1., 0.8);
+pub const LASER_RANGE: f32 = 600.;
+pub const LASER_TICK: f32 = 0.1;
+pub const LASER_WIDTH: f32 = 10.;
+pub const LASER_DAMAGE: i32 = 1;
+pub const FOCUSED_LASER_WIDTH: f32 = 4.;
+pub const FOCUSED_LASER_DAMAGE: i32 = 2;
+
+pub const MAX_OPTIONS: usize = 4;
+pub const OPTION_SIZE: f32 = 8.;
+pub const OPTION_COLOR: Color = Color::ORANGE;
+pub const OPTION_FIRE_RATE: f32 = 6.;
+pub const OPTION_FOLLOW_RATE: f32 = 12.;
+pub const OPTION_SPACING: f32 = 24.;
+pub const OPTION_OFFSET: f32 = -10.;
+pub const OPTION_FOCUS_SPACING: f32 = 10.;
+pub const OPTION_FOCUS_OFFSET: f32 = 24.;
+
+pub const STARTING_LIVES: u32 = 2;
+pub const STARTING_BOMBS: u32 = 3;
+pub const MAX_POWER: u32 = 100;
+pub const PRACTICE_MAX_LIVES: u32 = 9;
+pub const PRACTICE_MAX_BOMBS: u32 = 9;
+// One snapshot every 4 ticks keeps the last 10 seconds at 64 ticks per second.
+pub const REWIND_INTERVAL: u32 = 4;
+pub const REWIND_SNAPSHOTS: usize = 160;
+// Replays store a state hash once a second.
+pub const REPLAY_HASH_INTERVAL: u32 = 64;
+pub const HIT_INVULNERABILITY: f32 = 2.;
+pub const BOMB_INVULNERABILITY: f32 = 1.;
+pub const BARRIER_INVULNERABILITY: f32 = 3.;
+pub const BARRIER_RADIUS: f32 = 80.;
+pub const FRAGMENTS_PER_BOMB: u32 = 5;
+pub const FRAGMENTS_PER_LIFE: u32 = 5;
+
+pub const ITEM_SIZE: f32 = 6.;
+pub const ITEM_GRAVITY: f32 = 120.;
+pub const ITEM_MAX_FALL_SPEED: f32 = 90.;
+pub const ITEM_POP_SPEED: f32 = 60.;
+pub const ITEM_MAGNET_SPEED: f32 = 300.;
+pub const ITEM_MAGNET_RADIUS: f32 = 40.;
+pub const ITEM_COLLECT_RADIUS: f32 = 12.;
+pub const ITEM_COLLECTION_LINE: f32 = 100.;
+pub const POINT_ITEM_MIN_VALUE: i32 = 10;
+pub const POINT_ITEM_MAX_VALUE: i32 = 100;
+pub const POWER_ITEM_VALUE: u32 = 5;
+pub const POINT_ITEM_COLOR: Color = Color::CYAN;
+pub const POWER_ITEM_COLOR: Color = Color::ORANGE_RED;
+pub const BOMB_FRAGMENT_COLOR: Color = Color::LIME_GREEN;
+pub const LIFE_FRAGMENT_COLOR: Color = Color::PINK;
+
 pub const SHIP_SIZE: Vec2 = Vec2 { x: 40., y: 40. };
+pub const SHIP_DEFINITIONS: &[&str] = &[
+    "ships/arrow.ship.ron",
+    "ships/needle.ship.ron",
+    "ships/seeker.ship.ron",
+    "ships/lance.ship.ron",
+];
+
+pub const COMBO_TIMEOUT: f32 = 2.;
+pub const COMBO_STEP: u32 = 10;
+pub const COMBO_MAX_MULTIPLIER: u32 = 8;
+pub const COMBO_BAR_WIDTH: f32 = 80.;
+
+pub const HIGH_SCORE_COUNT: usize = 10;
 
 pub const WINDOW_PADDING: f32 = 25.;
-pub const WINDOW_SIZE: Vec2 = Vec2 { x: 300., y: 500. };
+pub const PLAYFIELD_SIZE: Vec2 = Vec2 { x: 300., y: 500. };
+pub const SIDE_PANEL_WIDTH: f32 = 120.;
+pub const SIDE_PANEL_COLOR: Color = Color::rgb(0.08, 0.08, 0.12);
+pub const LETTERBOX_COLOR: Color = Color::BLACK;
+pub const FRAME_Z: f32 = 50.;
+pub const BACKGROUND_Z: f32 = -10.;
+pub const PARTICLE_Z: f32 = 5.;
+pub const MAX_PARTICLES: usize = 1024;
+pub const BACKGROUND_TILE_SIZE: f32 = 200.;
+pub const BACKGROUND_SCROLL_SPEED: f32 = 40.;
+pub const BACKGROUND_LAYERS: [(f32, Color); 3] = [
+    (0.4, Color::rgb(0.35, 0.35, 0.45)),
+    (0.7, Color::rgba(1., 1., 1., 0.35)),
+    (1.2, Color::rgba(0.8, 0.9, 1., 0.2)),
+];
+pub const WINDOW_SIZE: Vec2 = Vec2 {
+    x: PLAYFIELD_SIZE.x + SIDE_PANEL_WIDTH,
+    y: PLAYFIELD_SIZE.y,
+};
+pub const WINDOW_SCALES: [f32; 4] = [1., 1.5, 2., 3.];
+pub const VOLUME_STEP: f32 = 0.1;
+pub const SCREEN_SHAKE_STEP: f32 = 0.25;
+
+pub const SFX_SHOT: &str = "audio/sfx/shot.wav";
+pub const SFX_EXPLOSION: &str = "audio/sfx/explosion.wav";
+pub const SFX_GRAZE: &str = "audio/sfx/graze.wav";
+pub const SFX_HIT: &str = "audio/sfx/hit.wav";
+pub const SFX_BOMB: &str = "audio/sfx/bomb.wav";
+pub const SFX_HOVER: &str = "audio/sfx/hover.wav";
+pub const SFX_PRESS: &str = "audio/sfx/press.wav";
+pub const MUSIC_MENU: &str = "audio/music/menu.wav";
+pub const MUSIC_STAGE: &str = "audio/music/stage.wav";
+pub const MUSIC_GAME_OVER: &str = "audio/music/gameover.wav";
+pub const MUSIC_CROSSFADE: f32 = 1.;
+
+pub const CAMERA_TRAUMA_DECAY: f32 = 1.5;
+pub const CAMERA_SHAKE_OFFSET: f32 = 12.;
+pub const CAMERA_SHAKE_ANGLE: f32 = 3.;
+pub const BOMB_IMPACT: (f32, u32) = (0.5, 4);
+pub const PLAYER_HIT_IMPACT: (f32, u32) = (0.7, 8);
+pub const BOSS_KILL_IMPACT: (f32, u32) = (1., 12);
+pub const HITSTOP_STEP: f32 = 0.25;
+
+pub const DEMO_IDLE_TIME: f32 = 20.;
+
+pub const CONSOLE_HISTORY: usize = 12;
@@ -0,0 +1,293 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::items::ItemCollection;
+use crate::lasers::{CurvyLaser, EnemyLaser};
+use crate::particles::Particle;
+use crate::ships::ShipStats;
+use crate::speed::frame_advance_input;
+use crate::weapons::Missile;
+use crate::GameplaySet;
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticId, Diagnostics, DiagnosticsStore, FrameTimeDiagnosticsPlugin,
+        LogDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    prelude::*,
+    utils::Instant,
+};
+
+const SET_DIAGNOSTICS: [(DiagnosticId, &str); 4] = [
+    (
+        DiagnosticId::from_u128(0x6d1b8a2e_4c0f_4f7e_9a51_3e0c2b7d1001),
+        "gameplay/input",
+    ),
+    (
+        DiagnosticId::from_u128(0x6d1b8a2e_4c0f_4f7e_9a51_3e0c2b7d1002),
+        "gameplay/simulate",
+    ),
+    (
+        DiagnosticId::from_u128(0x6d1b8a2e_4c0f_4f7e_9a51_3e0c2b7d1003),
+        "gameplay/damage",
+    ),
+    (
+        DiagnosticId::from_u128(0x6d1b8a2e_4c0f_4f7e_9a51_3e0c2b7d1004),
+        "gameplay/resolve",
+    ),
+];
+
+#[derive(Default, Resource)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Default, Resource)]
+pub struct SetTimings {
+    pub started: [Option<Instant>; 4],
+}
+
+#[derive(Component)]
+pub struct DebugText;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin);
+        if cfg!(debug_assertions) {
+            app.add_systems(Update, frame_advance_input);
+        }
+        // Logging every diagnostic each second is noisy, so it is opt-in.
+        if std::env::var_os("DODGE_LOG_DIAGNOSTICS").is_some() {
+            app.add_plugins(LogDiagnosticsPlugin::default());
+        }
+        for (id, name) in SET_DIAGNOSTICS {
+            app.register_diagnostic(Diagnostic::new(id, name, 20).with_suffix("ms"));
+        }
+
+        app.init_resource::<DebugOverlay>()
+            .init_resource::<SetTimings>()
+            .add_systems(Startup, setup_debug_overlay)
+            .add_systems(
+                FixedUpdate,
+                (
+                    begin_timing::<0>.before(GameplaySet::Input),
+                    end_timing::<0>
+                        .after(GameplaySet::Input)
+                        .before(GameplaySet::Simulate),
+                    begin_timing::<1>
+                        .after(GameplaySet::Input)
+                        .before(GameplaySet::Simulate),
+                    end_timing::<1>
+                        .after(GameplaySet::Simulate)
+                        .before(GameplaySet::Damage),
+                    begin_timing::<2>
+                        .after(GameplaySet::Simulate)
+                        .before(GameplaySet::Damage),
+                    end_timing::<2>
+                        .after(GameplaySet::Damage)
+                        .before(GameplaySet::Resolve),
+                    begin_timing::<3>
+                        .after(GameplaySet::Damage)
+                        .before(GameplaySet::Resolve),
+                    end_timing::<3>.after(GameplaySet::Resolve),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    (draw_hitboxes, update_debug_text).run_if(debug_overlay_enabled),
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn begin_timing<const SET: usize>(mut timings: ResMut<SetTimings>) {
+    timings.started[SET] = Some(Instant::now());
+}
+
+fn end_timing<const SET: usize>(timings: Res<SetTimings>, mut diagnostics: Diagnostics) {
+    if let Some(started) = timings.started[SET] {
+        diagnostics.add_measurement(SET_DIAGNOSTICS[SET].0, || {
+            started.elapsed().as_secs_f64() * 1000.
+        });
+    }
+}
+
+fn debug_overlay_enabled(overlay: Res<DebugOverlay>) -> bool {
+    overlay.enabled
+}
+
+fn setup_debug_overlay(mut commands: Commands) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font_size: 12.,
+                    color: Color::rgb(0.4, 1., 0.4),
+                    ..default()
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(4.),
+                bottom: Val::Px(4.),
+                ..default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.6).into(),
+            z_index: ZIndex::Global(100),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        DebugText,
+    ));
+}
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut query: Query<&mut Visibility, With<DebugText>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    overlay.enabled = !overlay.enabled;
+    for mut visibility in query.iter_mut() {
+        *visibility = if overlay.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_hitboxes(
+    mut gizmos: Gizmos,
+    collection: Res<ItemCollection>,
+    player_query: Query<&Transform, With<Player>>,
+    ship_query: Query<(&Transform, &Sprite, &ShipStats), With<Ship>>,
+    enemy_query: Query<&Transform, With<Enemy>>,
+    bullet_query: Query<&Transform, With<Bullet>>,
+    item_query: Query<&Transform, With<Item>>,
+    laser_query: Query<(&Transform, &EnemyLaser)>,
+    curvy_query: Query<&CurvyLaser>,
+) {
+    if let (Ok(player), Ok((ship, sprite, stats))) =
+        (player_query.get_single(), ship_query.get_single())
+    {
+        let player = player.translation.truncate();
+        let ship = ship.translation.truncate();
+        let ship_size = sprite.custom_size.unwrap_or(SHIP_SIZE);
+        gizmos.rect_2d(player, 0., Vec2::splat(stats.hitbox_size), Color::GREEN);
+        gizmos.rect_2d(ship, 0., Vec2::splat(stats.graze_size), Color::CYAN);
+        for laser in curvy_query.iter() {
+            gizmos.rect_2d(
+                ship,
+                0.,
+                Vec2::splat(stats.graze_size + laser.definition.width),
+                Color::rgba(0., 1., 1., 0.3),
+            );
+        }
+        gizmos.rect_2d(ship, 0., ship_size, Color::GRAY);
+        gizmos.circle_2d(ship, ITEM_COLLECT_RADIUS, Color::WHITE);
+        gizmos.circle_2d(ship, collection.magnet_radius, Color::rgba(1., 1., 1., 0.3));
+    }
+    for transform in enemy_query.iter() {
+        let position = transform.translation.truncate();
+        gizmos.rect_2d(position, 0., ENEMY_HITBOX, Color::RED);
+        gizmos.rect_2d(position, 0., ENEMY_SHOT_HITBOX, Color::YELLOW);
+    }
+    for transform in bullet_query.iter() {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.,
+            BULLET_HITBOX,
+            Color::YELLOW,
+        );
+    }
+    for transform in item_query.iter() {
+        gizmos.rect_2d(
+            transform.translation.truncate(),
+            0.,
+            Vec2::splat(ITEM_SIZE),
+            Color::WHITE,
+        );
+    }
+    for (transform, laser) in laser_query.iter() {
+        let (start, end) = laser.segment(transform.translation.truncate());
+        gizmos.line_2d(start, end, Color::RED);
+    }
+    for laser in curvy_query.iter() {
+        gizmos.linestrip_2d(laser.points.iter().copied(), Color::RED);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_debug_text(
+    diagnostics: Res<DiagnosticsStore>,
+    entity_query: Query<(
+        Has<Enemy>,
+        Has<Bullet>,
+        Has<EnemyLaser>,
+        Has<CurvyLaser>,
+        Has<Missile>,
+        Has<Item>,
+        Has<Particle>,
+    )>,
+    mut text_query: Query<&mut Text, With<DebugText>>,
+) {
+    let mut counts = [0; 7];
+    let mut entities = 0;
+    for components in entity_query.iter() {
+        entities += 1;
+        let flags = [
+            components.0,
+            components.1,
+            components.2,
+            components.3,
+            components.4,
+            components.5,
+            components.6,
+        ];
+        for (count, present) in counts.iter_mut().zip(flags) {
+            *count += present as usize;
+        }
+    }
+
+    let mut lines = vec![
+        format!(
+            "FPS: {:.0}",
+            diagnostics
+                .get(FrameTimeDiagnosticsPlugin::FPS)
+                .and_then(|fps| fps.smoothed())
+                .unwrap_or_default()
+        ),
+        format!("Entities: {}", entities),
+    ];
+    let labels = [
+        "Enemies",
+        "Bullets",
+        "Lasers",
+        "Curvy lasers",
+        "Missiles",
+        "Items",
+        "Particles",
+    ];
+    for (label, count) in labels.iter().zip(counts) {
+        lines.push(format!("{}: {}", label, count));
+    }
+    for (id, name) in SET_DIAGNOSTICS {
+        let average = diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.average())
+            .unwrap_or_default();
+        lines.push(format!("{}: {:.3}ms", name, average));
+    }
+
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = lines.join("\n");
+    }
+}
@@ -0,0 +1,180 @@
+use crate::constants::*;
+use crate::replay::{Replay, ReplayPlayback};
+use crate::ships::{SelectedShip, ShipDefinition, ShipDefinitions};
+use crate::{Difficulty, GameMode, GameState};
+use bevy::{input::mouse::MouseMotion, prelude::*};
+
+const DEMO_REPLAY: &[u8] = include_bytes!("../assets/demo/attract.dreplay");
+
+#[derive(Default, Resource)]
+pub struct DemoIdle {
+    pub timer: f32,
+}
+
+// The player's own choices are put aside while the demo plays and handed back
+// when it ends.
+#[derive(Resource)]
+pub struct DemoPlayback {
+    pub difficulty: Difficulty,
+    pub mode: GameMode,
+    pub ship: ShipDefinition,
+}
+
+#[derive(Component)]
+pub struct DemoBanner;
+
+pub fn reset_demo_idle(mut idle: ResMut<DemoIdle>) {
+    idle.timer = 0.;
+}
+
+pub fn tick_demo_idle(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut idle: ResMut<DemoIdle>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let active = keyboard_input.get_pressed().next().is_some()
+        || mouse_input.get_pressed().next().is_some()
+        || mouse_motion.read().count() > 0;
+    if active {
+        idle.timer = 0.;
+        return;
+    }
+    idle.timer += time.delta_seconds();
+    if idle.timer >= DEMO_IDLE_TIME {
+        next_state.set(GameState::Demo);
+    }
+}
+
+pub fn start_demo(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut mode: ResMut<GameMode>,
+    mut selected_ship: ResMut<SelectedShip>,
+    ships: Res<ShipDefinitions>,
+    definitions: Res<Assets<ShipDefinition>>,
+) {
+    let replay = match Replay::from_bytes(DEMO_REPLAY) {
+        Ok(replay) => replay,
+        Err(err) => {
+            warn!("Could not parse demo replay: {}", err);
+            next_state.set(GameState::Menu);
+            return;
+        }
+    };
+    let Some(ship) = ships
+        .handles
+        .iter()
+        .filter_map(|handle| definitions.get(handle))
+        .find(|definition| definition.id == replay.ship)
+    else {
+        warn!("Demo replay uses unknown ship '{}'", replay.ship);
+        next_state.set(GameState::Menu);
+        return;
+    };
+
+    commands.insert_resource(DemoPlayback {
+        difficulty: std::mem::replace(&mut *difficulty, replay.difficulty),
+        mode: std::mem::take(&mut *mode),
+        ship: std::mem::replace(&mut selected_ship.definition, ship.clone()),
+    });
+    commands.insert_resource(ReplayPlayback::new(replay));
+    commands.spawn((
+        TextBundle::from_section(
+            "DEMO",
+            TextStyle {
+                font_size: 40.,
+                color: Color::rgb(0.9, 0.9, 0.9),
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(WINDOW_PADDING),
+            left: Val::Px(WINDOW_PADDING),
+            ..default()
+        }),
+        DemoBanner,
+    ));
+}
+
+pub fn stop_demo(
+    mut commands: Commands,
+    demo: Option<Res<DemoPlayback>>,
+    mut difficulty: ResMut<Difficulty>,
+    mut mode: ResMut<GameMode>,
+    mut selected_ship: ResMut<SelectedShip>,
+    banner_query: Query<Entity, With<DemoBanner>>,
+) {
+    if let Some(demo) = demo {
+        *difficulty = demo.difficulty;
+        *mode = demo.mode;
+        selected_ship.definition = demo.ship.clone();
+        commands.remove_resource::<DemoPlayback>();
+    }
+    commands.remove_resource::<ReplayPlayback>();
+    for banner in banner_query.iter() {
+        commands.entity(banner).despawn_recursive();
+    }
+}
+
+pub fn finish_demo(
+    playback: Option<Res<ReplayPlayback>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if playback.is_some_and(|playback| playback.finished()) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+pub fn exit_demo_on_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+    {
+        next_state.set(GameState::Menu);
+    }
+}
+
+pub fn blink_demo_banner(time: Res<Time>, mut query: Query<&mut Visibility, With<DemoBanner>>) {
+    let visible = time.elapsed_seconds() % 1. < 0.6;
+    for mut visibility in query.iter_mut() {
+        *visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::{headless_app, load_definitions};
+    use crate::{setup_demo, RunSeed};
+
+    #[test]
+    fn demo_runs_with_the_replay_seed() {
+        let mut app = headless_app();
+        app.add_systems(OnEnter(GameState::Demo), setup_demo());
+        assert!(load_definitions(&mut app));
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Demo);
+        app.update();
+
+        let replay = Replay::from_bytes(DEMO_REPLAY).unwrap();
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::Demo
+        );
+        assert_eq!(app.world.resource::<RunSeed>().value, replay.seed);
+    }
+}
@@ -1,24 +1,155 @@
+use crate::combo::Combo;
 use crate::components::*;
 use crate::constants::*;
+use crate::events::*;
+use crate::patterns::{PatternScript, PatternStep};
+use crate::playfield::Playfield;
+use crate::{Difficulty, Score};
 use bevy::prelude::*;
 use bevy_prng::ChaCha8Rng;
 use bevy_rand::prelude::*;
 use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum EnemyArchetype {
+    Basic,
+    Fast,
+    Slow,
+    Turret,
+    Boss,
+}
+
+impl EnemyArchetype {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "basic" => Some(EnemyArchetype::Basic),
+            "fast" => Some(EnemyArchetype::Fast),
+            "slow" => Some(EnemyArchetype::Slow),
+            "turret" => Some(EnemyArchetype::Turret),
+            "boss" => Some(EnemyArchetype::Boss),
+            _ => None,
+        }
+    }
+
+    fn speed(self) -> f32 {
+        match self {
+            EnemyArchetype::Basic => 100.,
+            EnemyArchetype::Fast => FAST_SPEED,
+            EnemyArchetype::Slow => SLOW_SPEED,
+            EnemyArchetype::Turret => 60.,
+            EnemyArchetype::Boss => 40.,
+        }
+    }
+
+    fn health(self) -> i32 {
+        match self {
+            EnemyArchetype::Basic | EnemyArchetype::Fast | EnemyArchetype::Slow => ENEMY_HEALTH,
+            EnemyArchetype::Turret => TURRET_HEALTH,
+            EnemyArchetype::Boss => BOSS_HEALTH,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            EnemyArchetype::Basic => ENEMY_COLOR,
+            EnemyArchetype::Fast => FAST_ENEMY_COLOR,
+            EnemyArchetype::Slow => SLOW_ENEMY_COLOR,
+            EnemyArchetype::Turret => TURRET_COLOR,
+            EnemyArchetype::Boss => BOSS_COLOR,
+        }
+    }
+
+    fn hover(self) -> Option<Hover> {
+        match self {
+            EnemyArchetype::Turret => Some(Hover {
+                y: TURRET_HOVER_Y,
+                remaining: TURRET_HOVER_TIME,
+            }),
+            EnemyArchetype::Boss => Some(Hover {
+                y: BOSS_HOVER_Y,
+                remaining: f32::INFINITY,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct Hover {
+    pub y: f32,
+    pub remaining: f32,
+}
+
+#[derive(Component)]
+pub struct Boss;
+
+pub fn enemy_bundle(
+    asset_server: &AssetServer,
+    color: Color,
+    size: f32,
+    position: Vec2,
+) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::splat(size)),
+            ..default()
+        },
+        texture: asset_server.load("enemy.png"),
+        transform: Transform::from_translation(position.extend(0.))
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::PI)),
+        ..default()
+    }
+}
+
+pub fn spawn_enemy_archetype(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    archetype: EnemyArchetype,
+    position: Vec2,
+    speed_scale: f32,
+    pattern: Vec<PatternStep>,
+) {
+    let size = if archetype == EnemyArchetype::Boss {
+        BOSS_SIZE
+    } else {
+        20.
+    };
+    let mut enemy = commands.spawn((
+        enemy_bundle(asset_server, archetype.color(), size, position),
+        Enemy {
+            speed: archetype.speed() * speed_scale,
+            health: archetype.health(),
+        },
+    ));
+    if let Some(hover) = archetype.hover() {
+        enemy.insert(hover);
+    }
+    if archetype == EnemyArchetype::Boss {
+        enemy.insert(Boss);
+    }
+    if !pattern.is_empty() {
+        enemy.insert(PatternScript::new(pattern));
+    }
+}
 
 pub fn spawn_enemy(
     mut commands: Commands,
     mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
     query: Query<&Transform, With<Enemy>>,
     asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    playfield: Res<Playfield>,
 ) {
     if query.iter().len() < MAX_ENEMIES {
-        let mut x = rng.next_u32() as f32 % WINDOW_SIZE.x;
+        let mut x = rng.next_u32() as f32 % playfield.rect.width();
         x = if rng.next_u32() % 2 == 0 { -x } else { x };
         x = x.clamp(
-            -WINDOW_SIZE.x / 2. + WINDOW_PADDING,
-            WINDOW_SIZE.x / 2. - WINDOW_PADDING,
+            playfield.rect.min.x + WINDOW_PADDING,
+            playfield.rect.max.x - WINDOW_PADDING,
         );
-        let y = WINDOW_SIZE.y / 2. - 20.;
+        let y = playfield.top() - ENEMY_SPAWN_OFFSET;
 
         let speed = rng.next_u32() as f32 % ENEMY_SPEED;
         let color = {
@@ -31,33 +162,61 @@ pub fn spawn_enemy(
             }
         };
 
+        let speed = speed * difficulty.enemy_speed_scale();
         commands.spawn((
-            SpriteBundle {
-                sprite: Sprite {
-                    color,
-                    custom_size: Some(Vec2::from((20., 20.))),
-                    ..default()
-                },
-                texture: asset_server.load("enemy.png"),
-                transform: Transform::from_translation(Vec3::from((x, y, 0.)))
-                    .with_rotation(Quat::from_rotation_z(std::f32::consts::PI)),
-                ..default()
+            enemy_bundle(&asset_server, color, 20., Vec2::new(x, y)),
+            Enemy {
+                speed,
+                health: ENEMY_HEALTH,
             },
-            Enemy { speed },
         ));
     }
 }
 
 pub fn move_enemy(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &Enemy)>,
+    mut query: Query<(Entity, &mut Transform, &Enemy, Option<&mut Hover>)>,
     time: Res<Time>,
+    playfield: Res<Playfield>,
 ) {
-    for (entity, mut transform, enemy) in query.iter_mut() {
+    for (entity, mut transform, enemy, hover) in query.iter_mut() {
+        if let Some(mut hover) = hover {
+            if transform.translation.y <= hover.y && hover.remaining > 0. {
+                hover.remaining -= time.delta_seconds();
+                continue;
+            }
+        }
         transform.translation.y -= enemy.speed * time.delta_seconds();
 
-        if transform.translation.y < -(WINDOW_SIZE.y / 2.) {
+        if transform.translation.y < playfield.bottom() {
             commands.entity(entity).despawn();
         }
     }
 }
+
+pub fn apply_damage(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    combo: Res<Combo>,
+    mut enemy_query: Query<(&Transform, &mut Enemy, Has<Boss>)>,
+    mut damage_enemy: EventReader<DamageEnemy>,
+    mut enemy_destroyed: EventWriter<EnemyDestroyed>,
+) {
+    for event in damage_enemy.read() {
+        let Ok((transform, mut enemy, boss)) = enemy_query.get_mut(event.target) else {
+            continue;
+        };
+        if enemy.health <= 0 {
+            continue;
+        }
+        enemy.health -= event.amount;
+        if enemy.health <= 0 {
+            commands.entity(event.target).despawn();
+            score.value += 10 * combo.multiplier();
+            enemy_destroyed.send(EnemyDestroyed {
+                position: transform.translation,
+                boss,
+            });
+        }
+    }
+}
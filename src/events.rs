@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+#[derive(Event)]
+pub struct ShotFired;
+
+#[derive(Event)]
+pub struct BulletHit;
+
+#[derive(Event)]
+pub struct DamageEnemy {
+    pub target: Entity,
+    pub amount: i32,
+}
+
+#[derive(Event)]
+pub struct EnemyDestroyed {
+    pub position: Vec3,
+    pub boss: bool,
+}
+
+#[derive(Event)]
+pub struct Graze;
+
+#[derive(Event)]
+pub struct PlayerHit {
+    pub position: Vec3,
+}
+
+#[derive(Event)]
+pub struct BombUsed;
+
+#[derive(Event)]
+pub struct SaveReplay;
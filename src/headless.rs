@@ -0,0 +1,71 @@
+use crate::settings::Settings;
+use crate::ships::{ShipDefinition, ShipDefinitions};
+use crate::stage::{StageDefinition, StageDefinitions};
+use crate::GameplayPlugin;
+use bevy::{
+    hierarchy::HierarchyPlugin, input::InputPlugin, prelude::*, time::TimeUpdateStrategy,
+    transform::TransformPlugin,
+};
+use std::time::{Duration, Instant};
+
+const LOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+// The gameplay simulation without a window, renderer or audio, used by
+// replay verification and tests.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        InputPlugin,
+        HierarchyPlugin,
+        TransformPlugin,
+    ))
+    // Gameplay spawns handles to these; without a renderer they are only ever
+    // allocated, never loaded.
+    .init_asset::<Image>()
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>()
+    .insert_resource(Settings::default())
+    .add_plugins(GameplayPlugin);
+
+    // Every update advances exactly one fixed tick, as fast as it can run.
+    let timestep = app.world.resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn definitions_loaded(world: &World) -> bool {
+    let (Some(ships), Some(stages)) = (
+        world.get_resource::<ShipDefinitions>(),
+        world.get_resource::<StageDefinitions>(),
+    ) else {
+        return false;
+    };
+    let ship_assets = world.resource::<Assets<ShipDefinition>>();
+    let stage_assets = world.resource::<Assets<StageDefinition>>();
+    ships
+        .handles
+        .iter()
+        .all(|handle| ship_assets.contains(handle))
+        && stages
+            .handles
+            .iter()
+            .all(|handle| stage_assets.contains(handle))
+}
+
+// Runs can't start until the ship and stage definitions are in; returns false
+// if they don't arrive in time.
+pub fn load_definitions(app: &mut App) -> bool {
+    let started = Instant::now();
+    while !definitions_loaded(&app.world) {
+        if started.elapsed() > LOAD_TIMEOUT {
+            return false;
+        }
+        app.update();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    true
+}
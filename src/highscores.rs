@@ -0,0 +1,371 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::menu::{spawn_button, MenuData};
+use crate::ships::SelectedShip;
+use crate::storage::{data_path, load_ron, save_ron};
+use crate::{Difficulty, GameMode, RunFlags, Score};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const HIGH_SCORES_FILE: &str = "highscores.ron";
+const NAME_LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: i32,
+    #[serde(default)]
+    pub ship: String,
+    #[serde(default)]
+    pub assisted: bool,
+}
+
+#[derive(Default, Deserialize, Resource, Serialize)]
+pub struct HighScores {
+    tables: BTreeMap<(GameMode, Difficulty), Vec<HighScoreEntry>>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        load_ron(data_path(HIGH_SCORES_FILE)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        save_ron(data_path(HIGH_SCORES_FILE), self);
+    }
+
+    pub fn table(&self, mode: GameMode, difficulty: Difficulty) -> &[HighScoreEntry] {
+        self.tables
+            .get(&(mode, difficulty))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn qualifies(&self, mode: GameMode, difficulty: Difficulty, score: i32) -> bool {
+        let table = self.table(mode, difficulty);
+        score > 0
+            && (table.len() < HIGH_SCORE_COUNT
+                || table.last().map_or(true, |entry| score > entry.score))
+    }
+
+    pub fn insert(&mut self, mode: GameMode, difficulty: Difficulty, entry: HighScoreEntry) {
+        let table = self.tables.entry((mode, difficulty)).or_default();
+        table.push(entry);
+        table.sort_by(|a, b| b.score.cmp(&a.score));
+        table.truncate(HIGH_SCORE_COUNT);
+    }
+}
+
+#[derive(Resource)]
+pub struct NameEntry {
+    pub entity: Entity,
+    letters: [usize; 3],
+    cursor: usize,
+    submitted: bool,
+}
+
+impl NameEntry {
+    pub fn name(&self) -> String {
+        self.letters
+            .iter()
+            .map(|&letter| NAME_LETTERS[letter] as char)
+            .collect()
+    }
+
+    fn label(&self) -> String {
+        if self.submitted {
+            return format!("Saved as {}", self.name());
+        }
+        self.letters
+            .iter()
+            .enumerate()
+            .map(|(index, &letter)| {
+                let letter = NAME_LETTERS[letter] as char;
+                if index == self.cursor {
+                    format!("[{}]", letter)
+                } else {
+                    format!(" {} ", letter)
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(SystemParam)]
+pub struct RunResult<'w> {
+    score: Res<'w, Score>,
+    mode: Res<'w, GameMode>,
+    difficulty: Res<'w, Difficulty>,
+    selected_ship: Res<'w, SelectedShip>,
+    flags: Res<'w, RunFlags>,
+}
+
+impl RunResult<'_> {
+    fn qualifies(&self, high_scores: &HighScores) -> bool {
+        !self.flags.cheated && high_scores.qualifies(*self.mode, *self.difficulty, self.score.value)
+    }
+
+    fn submit(&self, name_entry: &mut NameEntry, high_scores: &mut HighScores) {
+        name_entry.submitted = true;
+        high_scores.insert(
+            *self.mode,
+            *self.difficulty,
+            HighScoreEntry {
+                name: name_entry.name(),
+                score: self.score.value,
+                ship: self.selected_ship.definition.id.clone(),
+                assisted: self.flags.assisted,
+            },
+        );
+        high_scores.save();
+    }
+}
+
+pub fn setup_name_entry(mut commands: Commands, result: RunResult, high_scores: Res<HighScores>) {
+    if !result.qualifies(&high_scores) {
+        return;
+    }
+
+    let mut name_entry = NameEntry {
+        entity: Entity::PLACEHOLDER,
+        letters: [0; 3],
+        cursor: 0,
+        submitted: false,
+    };
+    let label = name_entry.label();
+    name_entry.entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(170.),
+                width: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "New high score!",
+                TextStyle {
+                    font_size: 20.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font_size: 30.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                        ..default()
+                    },
+                ),
+                NameEntryText,
+            ));
+        })
+        .id();
+    commands.insert_resource(name_entry);
+}
+
+pub fn name_entry_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    name_entry: Option<ResMut<NameEntry>>,
+    mut high_scores: ResMut<HighScores>,
+    mut text_query: Query<&mut Text, With<NameEntryText>>,
+    result: RunResult,
+) {
+    let Some(mut name_entry) = name_entry else {
+        return;
+    };
+    if name_entry.submitted {
+        return;
+    }
+
+    let cursor = name_entry.cursor;
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        name_entry.letters[cursor] = (name_entry.letters[cursor] + 1) % NAME_LETTERS.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::Down) {
+        name_entry.letters[cursor] =
+            (name_entry.letters[cursor] + NAME_LETTERS.len() - 1) % NAME_LETTERS.len();
+    }
+    if keyboard_input.just_pressed(KeyCode::Left) {
+        name_entry.cursor = cursor.saturating_sub(1);
+    }
+    if keyboard_input.just_pressed(KeyCode::Right) {
+        name_entry.cursor = (cursor + 1).min(name_entry.letters.len() - 1);
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        result.submit(&mut name_entry, &mut high_scores);
+    }
+
+    if name_entry.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = name_entry.label();
+        }
+    }
+}
+
+pub fn cleanup_name_entry(
+    mut commands: Commands,
+    name_entry: Option<ResMut<NameEntry>>,
+    mut high_scores: ResMut<HighScores>,
+    result: RunResult,
+) {
+    let Some(mut name_entry) = name_entry else {
+        return;
+    };
+    if !name_entry.submitted {
+        result.submit(&mut name_entry, &mut high_scores);
+    }
+    commands.entity(name_entry.entity).despawn_recursive();
+    commands.remove_resource::<NameEntry>();
+}
+
+// The screen opens on the normal table for the current difficulty; either
+// can be switched from there.
+#[derive(Resource)]
+pub struct HighScoreView {
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+}
+
+pub fn setup_high_scores(mut commands: Commands, difficulty: Res<Difficulty>) {
+    let text_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(WINDOW_PADDING)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "High Scores",
+                    TextStyle {
+                        font_size: 40.,
+                        color: Color::rgb(0.5, 0.0, 0.0),
+                        ..default()
+                    },
+                ),
+                ColorText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 15.,
+                        color: Color::rgb(0.6, 0.6, 0.6),
+                        ..default()
+                    },
+                ),
+                HighScoreTitle,
+            ));
+            for rank in 0..HIGH_SCORE_COUNT {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 20.,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                            ..default()
+                        },
+                    ),
+                    HighScoreLine(rank),
+                ));
+            }
+        })
+        .id();
+
+    let button_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(WINDOW_PADDING),
+                width: Val::Percent(100.),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(10.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_button(parent, "Mode", MenuButton::HighScoreMode);
+            spawn_button(parent, "Difficulty", MenuButton::HighScoreDifficulty);
+            spawn_button(parent, "Back", MenuButton::Back);
+        })
+        .id();
+
+    commands.insert_resource(MenuData {
+        button_entity,
+        text_entity,
+    });
+    commands.insert_resource(HighScoreView {
+        mode: GameMode::Normal,
+        difficulty: *difficulty,
+    });
+}
+
+pub fn high_scores_menu(
+    mut view: ResMut<HighScoreView>,
+    interaction_query: Query<(&Interaction, &MenuButton), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, action) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            MenuButton::HighScoreMode => {
+                view.mode = match view.mode {
+                    GameMode::Normal => GameMode::Practice,
+                    GameMode::Practice => GameMode::Normal,
+                }
+            }
+            MenuButton::HighScoreDifficulty => view.difficulty = view.difficulty.next(),
+            _ => {}
+        }
+    }
+}
+
+pub fn update_high_scores(
+    view: Option<Res<HighScoreView>>,
+    high_scores: Res<HighScores>,
+    mut title_query: Query<&mut Text, (With<HighScoreTitle>, Without<HighScoreLine>)>,
+    mut line_query: Query<(&mut Text, &HighScoreLine), Without<HighScoreTitle>>,
+) {
+    let Some(view) = view.filter(|view| view.is_changed()) else {
+        return;
+    };
+    for mut text in title_query.iter_mut() {
+        text.sections[0].value = format!("{:?} - {:?}", view.mode, view.difficulty);
+    }
+    let table = high_scores.table(view.mode, view.difficulty);
+    for (mut text, HighScoreLine(rank)) in line_query.iter_mut() {
+        text.sections[0].value = match table.get(*rank) {
+            Some(entry) => format!(
+                "{:>2}. {:<3} {:>8} {}{}",
+                rank + 1,
+                entry.name,
+                entry.score,
+                entry.ship,
+                if entry.assisted { " (assisted)" } else { "" }
+            ),
+            None => format!("{:>2}. --- {:>8}", rank + 1, "-"),
+        };
+    }
+}
+
+pub fn cleanup_high_scores(mut commands: Commands, menu_data: Res<MenuData>) {
+    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.text_entity).despawn_recursive();
+    commands.remove_resource::<HighScoreView>();
+}
@@ -0,0 +1,101 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::*;
+use crate::playfield::Playfield;
+use crate::settings::Settings;
+use bevy::prelude::*;
+
+#[derive(Default, Resource)]
+pub struct CameraShake {
+    pub trauma: f32,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.);
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct Hitstop {
+    pub ticks: u32,
+    pub active: bool,
+}
+
+impl Hitstop {
+    pub fn trigger(&mut self, ticks: u32) {
+        self.ticks = self.ticks.max(ticks);
+    }
+}
+
+pub fn hitstop_inactive(hitstop: Res<Hitstop>) -> bool {
+    !hitstop.active
+}
+
+pub fn reset_impact(mut shake: ResMut<CameraShake>, mut hitstop: ResMut<Hitstop>) {
+    *shake = CameraShake::default();
+    *hitstop = Hitstop::default();
+}
+
+pub fn tick_hitstop(mut hitstop: ResMut<Hitstop>) {
+    hitstop.active = hitstop.ticks > 0;
+    hitstop.ticks = hitstop.ticks.saturating_sub(1);
+}
+
+// Hitstop only pauses whole simulation ticks, and input is neither sampled
+// nor recorded while it lasts, so replays see exactly the same tick stream.
+pub fn trigger_impacts(
+    settings: Res<Settings>,
+    mut shake: ResMut<CameraShake>,
+    mut hitstop: ResMut<Hitstop>,
+    mut destroyed: EventReader<EnemyDestroyed>,
+    mut player_hits: EventReader<PlayerHit>,
+    mut bombs: EventReader<BombUsed>,
+) {
+    let mut impacts = Vec::new();
+    if destroyed.read().any(|event| event.boss) {
+        impacts.push(BOSS_KILL_IMPACT);
+    }
+    if player_hits.read().count() > 0 {
+        impacts.push(PLAYER_HIT_IMPACT);
+    }
+    if bombs.read().count() > 0 {
+        impacts.push(BOMB_IMPACT);
+    }
+    for (trauma, ticks) in impacts {
+        shake.add_trauma(trauma);
+        hitstop.trigger((ticks as f32 * settings.hitstop).round() as u32);
+    }
+}
+
+pub fn shake_camera(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    playfield: Res<Playfield>,
+    mut shake: ResMut<CameraShake>,
+    mut query: Query<&mut Transform, With<MainCamera>>,
+) {
+    shake.trauma = (shake.trauma - CAMERA_TRAUMA_DECAY * time.delta_seconds()).max(0.);
+    let amount = shake.trauma * shake.trauma * settings.screen_shake;
+    let t = time.elapsed_seconds();
+    let offset = Vec2::new((t * 41.).sin(), (t * 53. + 1.7).sin()) * CAMERA_SHAKE_OFFSET * amount;
+    let angle = (t * 29. + 0.6).sin() * CAMERA_SHAKE_ANGLE.to_radians() * amount;
+
+    for mut transform in query.iter_mut() {
+        let base = playfield.screen().center();
+        transform.translation = (base + offset).extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
+
+pub fn reset_camera(
+    playfield: Res<Playfield>,
+    mut shake: ResMut<CameraShake>,
+    mut query: Query<&mut Transform, With<MainCamera>>,
+) {
+    shake.trauma = 0.;
+    for mut transform in query.iter_mut() {
+        transform.translation = playfield.screen().center().extend(transform.translation.z);
+        transform.rotation = Quat::IDENTITY;
+    }
+}
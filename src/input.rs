@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Actions(pub u8);
+
+impl Actions {
+    pub const UP: Self = Self(1 << 0);
+    pub const DOWN: Self = Self(1 << 1);
+    pub const LEFT: Self = Self(1 << 2);
+    pub const RIGHT: Self = Self(1 << 3);
+    pub const FOCUS: Self = Self(1 << 4);
+    pub const SHOOT: Self = Self(1 << 5);
+    pub const BOMB: Self = Self(1 << 6);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct PlayerInput {
+    pub current: Actions,
+    pub previous: Actions,
+}
+
+impl PlayerInput {
+    pub fn push(&mut self, actions: Actions) {
+        self.previous = self.current;
+        self.current = actions;
+    }
+
+    pub fn pressed(&self, action: Actions) -> bool {
+        self.current.contains(action)
+    }
+
+    pub fn just_pressed(&self, action: Actions) -> bool {
+        self.current.contains(action) && !self.previous.contains(action)
+    }
+}
+
+pub fn reset_input(mut input: ResMut<PlayerInput>) {
+    *input = PlayerInput::default();
+}
+
+pub fn sample_input(keyboard_input: Res<Input<KeyCode>>, mut input: ResMut<PlayerInput>) {
+    let mut actions = Actions::default();
+    if keyboard_input.any_pressed([KeyCode::W, KeyCode::Up]) {
+        actions.insert(Actions::UP);
+    }
+    if keyboard_input.any_pressed([KeyCode::S, KeyCode::Down]) {
+        actions.insert(Actions::DOWN);
+    }
+    if keyboard_input.any_pressed([KeyCode::A, KeyCode::Left]) {
+        actions.insert(Actions::LEFT);
+    }
+    if keyboard_input.any_pressed([KeyCode::D, KeyCode::Right]) {
+        actions.insert(Actions::RIGHT);
+    }
+    if keyboard_input.pressed(KeyCode::ShiftLeft) {
+        actions.insert(Actions::FOCUS);
+    }
+    if keyboard_input.pressed(KeyCode::Space) {
+        actions.insert(Actions::SHOOT);
+    }
+    if keyboard_input.pressed(KeyCode::X) {
+        actions.insert(Actions::BOMB);
+    }
+    input.push(actions);
+}
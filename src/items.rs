@@ -0,0 +1,196 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::EnemyDestroyed;
+use crate::player::PlayerStock;
+use crate::playfield::Playfield;
+use crate::Score;
+use bevy::prelude::*;
+use bevy_prng::ChaCha8Rng;
+use bevy_rand::prelude::*;
+use rand_core::RngCore;
+
+#[derive(Resource)]
+pub struct ItemCollection {
+    pub line_y: f32,
+    pub magnet_radius: f32,
+}
+
+impl Default for ItemCollection {
+    fn default() -> Self {
+        Self {
+            line_y: ITEM_COLLECTION_LINE,
+            magnet_radius: ITEM_MAGNET_RADIUS,
+        }
+    }
+}
+
+impl ItemKind {
+    fn color(self) -> Color {
+        match self {
+            ItemKind::Point => POINT_ITEM_COLOR,
+            ItemKind::Power => POWER_ITEM_COLOR,
+            ItemKind::BombFragment => BOMB_FRAGMENT_COLOR,
+            ItemKind::LifeFragment => LIFE_FRAGMENT_COLOR,
+        }
+    }
+}
+
+pub fn spawn_item(
+    commands: &mut Commands,
+    kind: ItemKind,
+    position: Vec3,
+    velocity: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: kind.color(),
+                    custom_size: Some(Vec2::splat(ITEM_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_translation(position.truncate().extend(0.5)),
+                ..default()
+            },
+            Item {
+                kind,
+                velocity,
+                magnetized: false,
+                auto_collected: false,
+            },
+        ))
+        .id()
+}
+
+pub fn drop_items(
+    mut commands: Commands,
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut enemy_destroyed: EventReader<EnemyDestroyed>,
+) {
+    for event in enemy_destroyed.read() {
+        let mut drops = vec![ItemKind::Point];
+        let roll = rng.next_u32() % 100;
+        if roll < 25 {
+            drops.push(ItemKind::Power);
+        }
+        if roll < 5 {
+            drops.push(ItemKind::BombFragment);
+        }
+        if roll < 3 {
+            drops.push(ItemKind::LifeFragment);
+        }
+
+        for kind in drops {
+            let spread = (rng.next_u32() % 61) as f32 - 30.;
+            spawn_item(
+                &mut commands,
+                kind,
+                event.position,
+                Vec2::new(spread, ITEM_POP_SPEED),
+            );
+        }
+    }
+}
+
+pub fn move_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    collection: Res<ItemCollection>,
+    ship_query: Query<&Transform, (With<Ship>, Without<Item>)>,
+    mut item_query: Query<(Entity, &mut Transform, &mut Item)>,
+    playfield: Res<Playfield>,
+) {
+    let ship = ship_query.get_single().ok().map(|ship| ship.translation);
+    let above_line = ship.map_or(false, |ship| ship.y > collection.line_y);
+
+    for (entity, mut transform, mut item) in item_query.iter_mut() {
+        if let Some(ship) = ship {
+            let distance = transform.translation.truncate().distance(ship.truncate());
+            if above_line {
+                item.magnetized = true;
+                item.auto_collected = true;
+            } else if distance < collection.magnet_radius {
+                item.magnetized = true;
+            }
+        }
+
+        match (item.magnetized, ship) {
+            (true, Some(ship)) => {
+                let direction =
+                    (ship.truncate() - transform.translation.truncate()).normalize_or_zero();
+                item.velocity = direction * ITEM_MAGNET_SPEED;
+            }
+            _ => {
+                item.velocity.x *= 1. - time.delta_seconds() * 2.;
+                item.velocity.y = (item.velocity.y - ITEM_GRAVITY * time.delta_seconds())
+                    .max(-ITEM_MAX_FALL_SPEED);
+            }
+        }
+
+        transform.translation += (item.velocity * time.delta_seconds()).extend(0.);
+        if transform.translation.y < playfield.bottom() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn collect_items(
+    mut commands: Commands,
+    collection: Res<ItemCollection>,
+    mut score: ResMut<Score>,
+    mut stock: ResMut<PlayerStock>,
+    ship_query: Query<&Transform, With<Ship>>,
+    item_query: Query<(Entity, &Transform, &Item)>,
+    playfield: Res<Playfield>,
+) {
+    let Ok(ship) = ship_query.get_single() else {
+        return;
+    };
+
+    for (entity, transform, item) in item_query.iter() {
+        let distance = transform
+            .translation
+            .truncate()
+            .distance(ship.translation.truncate());
+        if distance > ITEM_COLLECT_RADIUS {
+            continue;
+        }
+
+        commands.entity(entity).despawn();
+        match item.kind {
+            ItemKind::Point => {
+                score.value += point_value(
+                    transform.translation.y,
+                    playfield.bottom(),
+                    collection.line_y,
+                    item.auto_collected,
+                );
+            }
+            ItemKind::Power => {
+                stock.power = (stock.power + POWER_ITEM_VALUE).min(MAX_POWER);
+            }
+            ItemKind::BombFragment => {
+                stock.bomb_fragments += 1;
+                if stock.bomb_fragments >= FRAGMENTS_PER_BOMB {
+                    stock.bomb_fragments -= FRAGMENTS_PER_BOMB;
+                    stock.bombs += 1;
+                }
+            }
+            ItemKind::LifeFragment => {
+                stock.life_fragments += 1;
+                if stock.life_fragments >= FRAGMENTS_PER_LIFE {
+                    stock.life_fragments -= FRAGMENTS_PER_LIFE;
+                    stock.lives += 1;
+                }
+            }
+        }
+    }
+}
+
+fn point_value(height: f32, bottom: f32, line_y: f32, auto_collected: bool) -> i32 {
+    if auto_collected || height >= line_y {
+        return POINT_ITEM_MAX_VALUE;
+    }
+    let ratio = ((height - bottom) / (line_y - bottom)).clamp(0., 1.);
+    POINT_ITEM_MIN_VALUE + ((POINT_ITEM_MAX_VALUE - POINT_ITEM_MIN_VALUE) as f32 * ratio) as i32
+}
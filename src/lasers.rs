@@ -0,0 +1,359 @@
+use crate::combo::Combo;
+use crate::components::*;
+use crate::constants::*;
+use crate::events::{Graze, PlayerHit};
+use crate::input::PlayerInput;
+use crate::player::graze_value;
+use crate::playfield::Playfield;
+use crate::ships::ShipStats;
+use crate::Score;
+use bevy::{prelude::*, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum LaserMotion {
+    Straight,
+    Rotating { speed: f32 },
+    Sweeping { amplitude: f32, frequency: f32 },
+}
+
+impl LaserMotion {
+    pub fn offset(&self, elapsed: f32) -> f32 {
+        match *self {
+            LaserMotion::Straight => 0.,
+            LaserMotion::Rotating { speed } => speed * elapsed,
+            LaserMotion::Sweeping {
+                amplitude,
+                frequency,
+            } => amplitude * (elapsed * frequency * std::f32::consts::TAU).sin(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct LaserDefinition {
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+    pub length: f32,
+    pub width: f32,
+    pub telegraph: f32,
+    pub duration: f32,
+    pub motion: LaserMotion,
+}
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct EnemyLaser {
+    pub definition: LaserDefinition,
+    pub elapsed: f32,
+}
+
+impl EnemyLaser {
+    pub fn is_active(&self) -> bool {
+        self.elapsed >= self.definition.telegraph
+    }
+
+    pub fn angle(&self) -> f32 {
+        let definition = &self.definition;
+        (definition.angle + definition.motion.offset(self.elapsed)).to_radians()
+    }
+
+    pub fn segment(&self, origin: Vec2) -> (Vec2, Vec2) {
+        let direction = Vec2::from_angle(self.angle()).rotate(Vec2::NEG_Y);
+        (origin, origin + direction * self.definition.length)
+    }
+}
+
+pub fn spawn_enemy_laser(
+    commands: &mut Commands,
+    definition: LaserDefinition,
+    origin: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: LASER_WARNING_COLOR,
+                    custom_size: Some(Vec2::new(LASER_WARNING_WIDTH, definition.length)),
+                    anchor: Anchor::TopCenter,
+                    ..default()
+                },
+                transform: Transform::from_translation(origin.extend(0.6))
+                    .with_rotation(Quat::from_rotation_z(definition.angle.to_radians())),
+                ..default()
+            },
+            EnemyLaser {
+                definition,
+                elapsed: 0.,
+            },
+        ))
+        .id()
+}
+
+pub fn update_enemy_lasers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Sprite, &mut EnemyLaser)>,
+) {
+    for (entity, mut transform, mut sprite, mut laser) in query.iter_mut() {
+        laser.elapsed += time.delta_seconds();
+        if laser.elapsed >= laser.definition.telegraph + laser.definition.duration {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.rotation = Quat::from_rotation_z(laser.angle());
+        if laser.is_active() {
+            sprite.color = LASER_ACTIVE_COLOR;
+            sprite.custom_size = Some(Vec2::new(laser.definition.width, laser.definition.length));
+        }
+    }
+}
+
+pub fn segment_circle(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> bool {
+    let segment = end - start;
+    let t = if segment.length_squared() > 0. {
+        ((center - start).dot(segment) / segment.length_squared()).clamp(0., 1.)
+    } else {
+        0.
+    };
+    (start + segment * t).distance(center) <= radius
+}
+
+pub fn segment_rect(start: Vec2, end: Vec2, rect: Rect) -> bool {
+    let segment = end - start;
+    let axes = [
+        (start.x, segment.x, rect.min.x, rect.max.x),
+        (start.y, segment.y, rect.min.y, rect.max.y),
+    ];
+    let (mut t_min, mut t_max) = (0f32, 1f32);
+    for (origin, direction, min, max) in axes {
+        if direction == 0. {
+            if origin < min || origin > max {
+                return false;
+            }
+            continue;
+        }
+        let near = (min - origin) / direction;
+        let far = (max - origin) / direction;
+        t_min = t_min.max(near.min(far));
+        t_max = t_max.min(near.max(far));
+        if t_min > t_max {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn collide_enemy_lasers(
+    player_query: Query<&Transform, (With<Player>, Without<Invulnerable>)>,
+    stats_query: Query<&ShipStats>,
+    laser_query: Query<(&Transform, &EnemyLaser)>,
+    mut player_hit: EventWriter<PlayerHit>,
+) {
+    let (Ok(player), Ok(stats)) = (player_query.get_single(), stats_query.get_single()) else {
+        return;
+    };
+
+    let hit = laser_query.iter().any(|(transform, laser)| {
+        let (start, end) = laser.segment(transform.translation.truncate());
+        laser.is_active()
+            && segment_circle(
+                start,
+                end,
+                player.translation.truncate(),
+                (laser.definition.width + stats.hitbox_size) / 2.,
+            )
+    });
+    if hit {
+        player_hit.send(PlayerHit {
+            position: player.translation,
+        });
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct CurvyLaserDefinition {
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+    pub speed: f32,
+    pub segments: usize,
+    pub segment_length: f32,
+    pub width: f32,
+    pub duration: f32,
+    pub motion: LaserMotion,
+}
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct CurvyLaser {
+    pub definition: CurvyLaserDefinition,
+    pub elapsed: f32,
+    pub points: Vec<Vec2>,
+    // Past head positions, newest first, trimmed to the length of the body.
+    pub trail: Vec<Vec2>,
+}
+
+#[derive(Component)]
+pub struct CurvyLaserSegment;
+
+impl CurvyLaser {
+    pub fn segments(&self) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        self.points.windows(2).map(|pair| (pair[0], pair[1]))
+    }
+
+    pub fn touches(&self, center: Vec2, radius: f32) -> bool {
+        self.segments()
+            .any(|(start, end)| segment_circle(start, end, center, radius))
+    }
+
+    pub fn overlaps(&self, rect: Rect) -> bool {
+        self.segments()
+            .any(|(start, end)| segment_rect(start, end, rect))
+    }
+
+    // Places the points at `segment_length` intervals along the trail, so the
+    // body runs exactly where the head has been. Points the trail doesn't
+    // reach yet wait at its oldest end.
+    fn trace_trail(&mut self) {
+        let spacing = self.definition.segment_length;
+        let count = self.definition.segments;
+        let mut points = vec![self.trail[0]];
+        let mut remaining = spacing;
+        let mut used = self.trail.len();
+        for (index, pair) in self.trail.windows(2).enumerate() {
+            let direction = pair[1] - pair[0];
+            let length = direction.length();
+            if length == 0. {
+                continue;
+            }
+            let mut travelled = 0.;
+            while points.len() <= count && length - travelled >= remaining {
+                travelled += remaining;
+                points.push(pair[0] + direction * (travelled / length));
+                remaining = spacing;
+            }
+            if points.len() > count {
+                used = index + 2;
+                break;
+            }
+            remaining -= length - travelled;
+        }
+        points.resize(count + 1, *self.trail.last().unwrap());
+        self.trail.truncate(used);
+        self.points = points;
+    }
+}
+
+pub fn spawn_curvy_laser(
+    commands: &mut Commands,
+    definition: CurvyLaserDefinition,
+    origin: Vec2,
+) -> Entity {
+    commands
+        .spawn((
+            SpatialBundle::default(),
+            CurvyLaser {
+                definition,
+                elapsed: 0.,
+                points: vec![origin; definition.segments + 1],
+                trail: vec![origin],
+            },
+        ))
+        .with_children(|parent| {
+            for _ in 0..definition.segments {
+                parent.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: CURVY_LASER_COLOR,
+                            custom_size: Some(Vec2::new(definition.width, 0.)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(origin.extend(0.6)),
+                        ..default()
+                    },
+                    CurvyLaserSegment,
+                ));
+            }
+        })
+        .id()
+}
+
+pub fn update_curvy_lasers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut laser_query: Query<(Entity, &mut CurvyLaser, &Children)>,
+    mut segment_query: Query<(&mut Transform, &mut Sprite), With<CurvyLaserSegment>>,
+    playfield: Res<Playfield>,
+) {
+    for (entity, mut laser, children) in laser_query.iter_mut() {
+        laser.elapsed += time.delta_seconds();
+        let definition = laser.definition;
+        let heading = (definition.angle + definition.motion.offset(laser.elapsed)).to_radians();
+        let head = laser.trail[0]
+            + Vec2::from_angle(heading).rotate(Vec2::NEG_Y)
+                * definition.speed
+                * time.delta_seconds();
+        laser.trail.insert(0, head);
+        laser.trace_trail();
+
+        let tail = *laser.points.last().unwrap();
+        let margin = definition.width;
+        if laser.elapsed >= definition.duration || !playfield.contains(tail, margin) {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        for (&child, (start, end)) in children.iter().zip(laser.segments()) {
+            let Ok((mut transform, mut sprite)) = segment_query.get_mut(child) else {
+                continue;
+            };
+            let segment = start - end;
+            transform.translation = ((start + end) / 2.).extend(0.6);
+            if segment.length_squared() > 0. {
+                transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_between(segment));
+            }
+            sprite.custom_size = Some(Vec2::new(definition.width, segment.length()));
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn collide_curvy_lasers(
+    mut commands: Commands,
+    player_query: Query<&Transform, (With<Player>, Without<Invulnerable>)>,
+    ship_query: Query<(&Transform, &ShipStats), With<Ship>>,
+    laser_query: Query<(Entity, &CurvyLaser, Has<Grazed>)>,
+    mut score: ResMut<Score>,
+    combo: Res<Combo>,
+    input: Res<PlayerInput>,
+    mut player_hit: EventWriter<PlayerHit>,
+    mut graze: EventWriter<Graze>,
+) {
+    let (Ok(player), Ok((ship, stats))) = (player_query.get_single(), ship_query.get_single())
+    else {
+        return;
+    };
+    let center = player.translation.truncate();
+
+    // Grazing uses the same box around the ship as grazing enemies does.
+    for (entity, laser, grazed) in laser_query.iter() {
+        if laser.touches(center, (laser.definition.width + stats.hitbox_size) / 2.) {
+            player_hit.send(PlayerHit {
+                position: player.translation,
+            });
+            return;
+        }
+        let graze_area = Rect::from_center_size(
+            ship.translation.truncate(),
+            Vec2::splat(stats.graze_size + laser.definition.width),
+        );
+        if laser.overlaps(graze_area) {
+            if !grazed {
+                commands.entity(entity).insert(Grazed);
+                graze.send(Graze);
+            }
+            score.value += graze_value(&combo, &input);
+        }
+    }
+}
@@ -0,0 +1,658 @@
+pub mod audio;
+pub mod background;
+pub mod combo;
+pub mod components;
+pub mod console;
+pub mod constants;
+pub mod debug;
+pub mod demo;
+pub mod enemies;
+pub mod events;
+pub mod headless;
+pub mod highscores;
+pub mod impact;
+pub mod input;
+pub mod items;
+pub mod lasers;
+pub mod menu;
+pub mod options;
+pub mod particles;
+pub mod patterns;
+pub mod pause;
+pub mod player;
+pub mod playfield;
+pub mod practice;
+pub mod replay;
+pub mod ron_loader;
+pub mod settings;
+pub mod ships;
+pub mod snapshot;
+pub mod speed;
+pub mod stage;
+pub mod stats;
+pub mod storage;
+pub mod weapons;
+
+use audio::*;
+use background::*;
+use bevy::{prelude::*, transform::TransformSystem};
+use bevy_prng::ChaCha8Rng;
+use bevy_rand::prelude::*;
+use combo::*;
+use components::*;
+use console::{apply_console_kills, console_closed, Console};
+use constants::*;
+use demo::*;
+use enemies::*;
+use events::*;
+use highscores::*;
+use impact::*;
+use input::*;
+use items::*;
+use lasers::*;
+use menu::*;
+use options::*;
+use particles::*;
+use patterns::*;
+use pause::*;
+use player::*;
+use playfield::*;
+use practice::*;
+use rand_core::{RngCore, SeedableRng};
+use replay::*;
+use ron_loader::RonAssetLoader;
+use serde::{Deserialize, Serialize};
+use settings::*;
+use ships::*;
+use snapshot::*;
+use speed::*;
+use stage::*;
+use stats::*;
+use weapons::*;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+pub enum GameState {
+    #[default]
+    Menu,
+    ShipSelect,
+    PracticeSelect,
+    Running,
+    Restarting,
+    GameOver,
+    HighScores,
+    Settings,
+    Demo,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Resource, Serialize,
+)]
+pub enum GameMode {
+    #[default]
+    Normal,
+    Practice,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, Ord, PartialEq, PartialOrd, Resource, Serialize,
+)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn enemy_speed_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.75,
+            Difficulty::Normal => 1.,
+            Difficulty::Hard => 1.25,
+        }
+    }
+}
+
+#[derive(SystemSet, Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum GameplaySet {
+    Input,
+    Simulate,
+    Damage,
+    Resolve,
+}
+
+// No further ticks run once the run has asked to leave gameplay, so a replay
+// ends on the same tick however many ticks the last frame held.
+fn in_gameplay(state: Res<State<GameState>>, next_state: Res<NextState<GameState>>) -> bool {
+    matches!(state.get(), GameState::Running | GameState::Demo) && next_state.0.is_none()
+}
+
+#[derive(Resource)]
+pub struct Score {
+    pub value: i32,
+}
+
+#[derive(Default, Resource)]
+pub struct RunSeed {
+    pub value: u64,
+}
+
+#[derive(Default, Resource)]
+pub struct RunFlags {
+    pub cheated: bool,
+    pub assisted: bool,
+}
+
+// Everything a run needs to simulate, with no window, input devices or audio,
+// so that replays can be checked headlessly by the same code the game runs.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Score { value: 0 })
+            .init_resource::<GameMode>()
+            .init_resource::<Difficulty>()
+            .init_resource::<RunSeed>()
+            .init_resource::<RunFlags>()
+            .init_resource::<GodMode>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<FrameAdvance>()
+            .init_resource::<PracticeConfig>()
+            .init_resource::<RewindBuffer>()
+            .init_resource::<RunStats>()
+            .init_resource::<Combo>()
+            .init_resource::<PlayerStock>()
+            .init_resource::<SelectedShip>()
+            .init_resource::<StageDirector>()
+            .init_resource::<Playfield>()
+            .init_resource::<BackgroundScroll>()
+            .init_resource::<CameraShake>()
+            .init_resource::<Hitstop>()
+            .init_resource::<ItemCollection>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<Console>()
+            .add_event::<ShotFired>()
+            .add_event::<BulletHit>()
+            .add_event::<DamageEnemy>()
+            .add_event::<EnemyDestroyed>()
+            .add_event::<Graze>()
+            .add_event::<PlayerHit>()
+            .add_event::<BombUsed>()
+            .add_event::<SaveReplay>()
+            .add_plugins(EntropyPlugin::<ChaCha8Rng>::default())
+            .init_asset::<ShipDefinition>()
+            .register_asset_loader(RonAssetLoader::<ShipDefinition>::default())
+            .init_asset::<StageDefinition>()
+            .register_asset_loader(RonAssetLoader::<StageDefinition>::default())
+            .add_state::<GameState>()
+            .add_state::<PauseState>()
+            .add_systems(
+                Startup,
+                (
+                    load_ship_definitions,
+                    load_stage_definitions,
+                    load_bullet_assets,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Running), setup_run())
+            .add_systems(OnExit(GameState::Running), (cleanup_game, reset_pause))
+            .add_systems(OnEnter(GameState::Restarting), restart_run)
+            .configure_sets(
+                FixedUpdate,
+                (
+                    GameplaySet::Input,
+                    GameplaySet::Simulate,
+                    GameplaySet::Damage,
+                    GameplaySet::Resolve,
+                )
+                    .chain()
+                    .run_if(in_gameplay)
+                    .run_if(in_state(PauseState::Unpaused))
+                    .run_if(console_closed)
+                    .run_if(frame_advance_ready)
+                    .run_if(not_rewinding)
+                    .run_if(hitstop_inactive),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick_hitstop
+                        .before(GameplaySet::Input)
+                        .run_if(frame_advance_ready),
+                    consume_frame_step
+                        .after(tick_hitstop)
+                        .after(GameplaySet::Resolve),
+                    rewind
+                        .before(GameplaySet::Input)
+                        .run_if(resource_equals(GameMode::Practice)),
+                )
+                    .run_if(in_gameplay)
+                    .run_if(in_state(PauseState::Unpaused))
+                    .run_if(console_closed),
+            )
+            // Gameplay runs in a fixed order so that replays simulate identically.
+            .add_systems(
+                FixedUpdate,
+                (
+                    (move_player, collide_player, use_bomb, tick_invulnerability).chain(),
+                    (
+                        fire_bullet,
+                        (sync_options, move_options, fire_options).chain(),
+                        move_bullets,
+                        collide_bullets,
+                        (steer_missiles, collide_missiles).chain(),
+                        update_laser,
+                    )
+                        .chain(),
+                    (
+                        fast_forward_stage,
+                        move_enemy,
+                        spawn_enemy,
+                        run_stage,
+                        run_patterns,
+                        update_enemy_lasers,
+                        collide_enemy_lasers,
+                        update_curvy_lasers,
+                        collide_curvy_lasers,
+                    )
+                        .chain(),
+                    (move_items, collect_items).chain(),
+                    track_time,
+                )
+                    .chain()
+                    .in_set(GameplaySet::Simulate),
+            )
+            .add_systems(
+                StageFastForward,
+                (
+                    move_enemy,
+                    run_stage,
+                    run_patterns,
+                    update_enemy_lasers,
+                    update_curvy_lasers,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (apply_console_kills, apply_damage, resolve_player_hit)
+                    .chain()
+                    .in_set(GameplaySet::Damage),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    track_events,
+                    update_combo,
+                    drop_items,
+                    trigger_impacts,
+                    record_rewind.run_if(resource_equals(GameMode::Practice)),
+                    record_state_hash.run_if(in_state(GameState::Running)),
+                )
+                    .chain()
+                    .in_set(GameplaySet::Resolve),
+            );
+    }
+}
+
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(GameplayPlugin)
+            .init_resource::<QuickSave>()
+            .init_resource::<DemoIdle>()
+            .init_resource::<MasterVolume>()
+            .init_resource::<MusicVolume>()
+            .init_resource::<SfxVolume>()
+            .init_resource::<ParticleRng>()
+            .add_systems(
+                Startup,
+                (
+                    setup_camera,
+                    setup_playfield_frame,
+                    setup_background,
+                    load_sound_effects,
+                ),
+            )
+            .add_systems(OnEnter(GameState::Menu), (setup_menu, reset_demo_idle))
+            .add_systems(OnExit(GameState::Menu), cleanup_menu)
+            .add_systems(OnEnter(GameState::Demo), setup_demo())
+            .add_systems(
+                OnExit(GameState::Demo),
+                (cleanup_game, stop_demo, reset_camera),
+            )
+            .add_systems(OnExit(GameState::Running), reset_camera)
+            .add_systems(OnEnter(PauseState::Paused), setup_pause_menu)
+            .add_systems(OnExit(PauseState::Paused), cleanup_pause_menu)
+            .add_systems(OnEnter(GameState::Settings), setup_settings_menu)
+            .add_systems(OnExit(GameState::Settings), cleanup_settings_menu)
+            .add_systems(OnEnter(PauseState::Settings), setup_settings_menu)
+            .add_systems(OnExit(PauseState::Settings), cleanup_settings_menu)
+            .add_systems(OnEnter(GameState::GameOver), (game_over, setup_name_entry))
+            .add_systems(
+                OnExit(GameState::GameOver),
+                (cleanup_name_entry, cleanup_game_over).chain(),
+            )
+            .add_systems(OnEnter(GameState::ShipSelect), setup_ship_select)
+            .add_systems(OnExit(GameState::ShipSelect), cleanup_ship_select)
+            .add_systems(OnEnter(GameState::PracticeSelect), setup_practice_menu)
+            .add_systems(OnExit(GameState::PracticeSelect), cleanup_practice_menu)
+            .add_systems(OnEnter(GameState::HighScores), setup_high_scores)
+            .add_systems(OnExit(GameState::HighScores), cleanup_high_scores)
+            .add_systems(
+                FixedUpdate,
+                (
+                    (sample_input, record_input)
+                        .chain()
+                        .run_if(in_state(GameState::Running)),
+                    (play_replay_input, finish_demo)
+                        .chain()
+                        .run_if(in_state(GameState::Demo)),
+                )
+                    .in_set(GameplaySet::Input),
+            )
+            .add_systems(Update, (update_score, update_stock, update_combo_hud))
+            .add_systems(
+                Update,
+                (menu, update_difficulty_label, tick_demo_idle).run_if(in_state(GameState::Menu)),
+            )
+            .add_systems(
+                Update,
+                (exit_demo_on_input, blink_demo_banner).run_if(in_state(GameState::Demo)),
+            )
+            .add_systems(Update, menu.run_if(in_state(GameState::GameOver)))
+            .add_systems(
+                Update,
+                (menu, high_scores_menu, update_high_scores)
+                    .chain()
+                    .run_if(in_state(GameState::HighScores)),
+            )
+            .add_systems(Update, menu.run_if(in_state(GameState::ShipSelect)))
+            .add_systems(
+                Update,
+                (menu, update_speed_label).run_if(in_state(GameState::PracticeSelect)),
+            )
+            .add_systems(Update, (practice_menu, update_practice_labels))
+            .add_systems(Update, menu.run_if(in_state(GameState::Settings)))
+            .add_systems(
+                Update,
+                (settings_menu, update_settings_labels, update_hitbox_display),
+            )
+            .add_systems(Update, apply_game_speed)
+            .add_systems(
+                Update,
+                (
+                    scroll_background,
+                    shake_camera,
+                    spawn_gameplay_particles,
+                    update_particles,
+                )
+                    .run_if(in_state(PauseState::Unpaused)),
+            )
+            .add_systems(
+                PostUpdate,
+                emit_particles
+                    .after(TransformSystem::TransformPropagate)
+                    .run_if(in_state(PauseState::Unpaused)),
+            )
+            .add_systems(
+                Update,
+                (
+                    apply_volume_settings,
+                    (play_gameplay_sfx, play_menu_sfx, change_music, fade_music),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (name_entry_input, save_replay).run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(
+                Update,
+                (toggle_pause.run_if(console_closed), pause_on_focus_loss)
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                Update,
+                rewind_input
+                    .run_if(in_state(GameState::Running))
+                    .run_if(resource_equals(GameMode::Practice)),
+            )
+            .add_systems(
+                Update,
+                quick_save
+                    .run_if(in_state(GameState::Running))
+                    .run_if(in_state(PauseState::Unpaused))
+                    .run_if(console_closed)
+                    .run_if(resource_equals(GameMode::Practice)),
+            )
+            .add_systems(
+                Update,
+                menu.run_if(
+                    in_state(GameState::Running).and_then(not(in_state(PauseState::Unpaused))),
+                ),
+            );
+    }
+}
+
+// Shared by normal runs, the attract-mode demo and replay verification.
+fn setup_run() -> impl IntoSystemConfigs<()> {
+    (
+        start_run,
+        (
+            start_recording,
+            reset_stats,
+            reset_combo,
+            reset_stock,
+            reset_stage,
+            reset_input,
+            reset_rewind,
+            spawn_player,
+            spawn_hud,
+        ),
+        (reset_background, reset_impact),
+        apply_deferred,
+        spawn_options,
+    )
+        .chain()
+}
+
+// start_demo inserts the replay through commands, and start_run has to see it
+// to take the replay's seed.
+pub fn setup_demo() -> impl IntoSystemConfigs<()> {
+    (start_demo, apply_deferred, setup_run()).chain()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_run(
+    mut rng: ResMut<GlobalEntropy<ChaCha8Rng>>,
+    mut seed: ResMut<RunSeed>,
+    mut score: ResMut<Score>,
+    mut flags: ResMut<RunFlags>,
+    mode: Res<GameMode>,
+    god: Res<GodMode>,
+    mut speed: ResMut<GameSpeed>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    score.value = 0;
+    // Slower speeds are a practice aid; normal runs always start at full
+    // speed.
+    if *mode == GameMode::Normal {
+        speed.factor = 1.;
+    }
+    *flags = RunFlags {
+        cheated: god.enabled,
+        assisted: speed.assisted(),
+    };
+    seed.value = match playback {
+        Some(playback) => playback.replay.seed,
+        None => rng.next_u64(),
+    };
+    *rng = GlobalEntropy::<ChaCha8Rng>::seed_from_u64(seed.value);
+}
+
+fn update_score(mut query: Query<&mut Text, With<ScoreText>>, score: Res<Score>) {
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!("Score: {}", score.value);
+    }
+}
+
+fn update_stock(mut query: Query<&mut Text, With<StockText>>, stock: Res<PlayerStock>) {
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format!(
+            "Lives: {}\nBombs: {}\nPower: {}",
+            stock.lives, stock.bombs, stock.power
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn game_over(
+    mut commands: Commands,
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    speed: Res<GameSpeed>,
+    flags: Res<RunFlags>,
+    mode: Res<GameMode>,
+) {
+    let text_style = TextStyle {
+        font_size: 15.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+    let results = [
+        format!("Score: {}", score.value),
+        format!("Time survived: {:.1}s", stats.time_survived),
+        format!("Enemies killed: {}", stats.enemies_killed),
+        format!("Shots fired: {}", stats.shots_fired),
+        format!("Accuracy: {:.0}%", stats.accuracy()),
+        format!("Grazes: {}", stats.grazes),
+        format!("Focus time: {:.0}%", stats.focus_percent()),
+        format!("Max combo: {}", stats.max_combo),
+        format!("Seed: {}", seed.value),
+        format!("Difficulty: {:?}", *difficulty),
+        if flags.assisted {
+            format!("{} (assisted)", speed.label())
+        } else {
+            speed.label()
+        },
+    ];
+
+    let text_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                padding: UiRect::all(Val::Px(WINDOW_PADDING)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "You Failed",
+                    TextStyle {
+                        font_size: 40.,
+                        color: Color::rgb(0.5, 0.0, 0.0),
+                        ..default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                ColorText,
+            ));
+            for line in results {
+                parent.spawn(TextBundle::from_section(line, text_style.clone()));
+            }
+        })
+        .id();
+
+    let button_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(WINDOW_PADDING),
+                width: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            spawn_button(parent, "Retry", MenuButton::Retry);
+            spawn_button(parent, "Main Menu", MenuButton::MainMenu);
+            // Rewinds, quick loads and console commands change the run in ways
+            // the recorded inputs can't reproduce.
+            if *mode == GameMode::Normal && !flags.cheated {
+                spawn_button(parent, "Save Replay", MenuButton::SaveReplay);
+            }
+        })
+        .id();
+
+    commands.insert_resource(MenuData {
+        button_entity,
+        text_entity,
+    });
+}
+
+fn cleanup_game_over(mut commands: Commands, menu_data: Res<MenuData>, mut score: ResMut<Score>) {
+    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.text_entity).despawn_recursive();
+    score.value = 0;
+}
+
+#[allow(clippy::type_complexity)]
+fn cleanup_game(
+    mut commands: Commands,
+    bullet_query: Query<Entity, With<Bullet>>,
+    enemy_query: Query<Entity, With<Enemy>>,
+    ship_query: Query<Entity, With<Ship>>,
+    item_query: Query<Entity, With<Item>>,
+    option_query: Query<Entity, With<PlayerOption>>,
+    weapon_query: Query<
+        Entity,
+        Or<(
+            With<Missile>,
+            With<PlayerLaser>,
+            With<EnemyLaser>,
+            With<CurvyLaser>,
+        )>,
+    >,
+    hud_query: Query<Entity, With<Hud>>,
+) {
+    commands.entity(ship_query.single()).despawn_recursive();
+    for hud in hud_query.iter() {
+        commands.entity(hud).despawn_recursive();
+    }
+    for enemy in enemy_query.iter() {
+        commands.entity(enemy).despawn();
+    }
+    for bullet in bullet_query.iter() {
+        commands.entity(bullet).despawn();
+    }
+    for item in item_query.iter() {
+        commands.entity(item).despawn();
+    }
+    for option in option_query.iter() {
+        commands.entity(option).despawn();
+    }
+    for weapon in weapon_query.iter() {
+        commands.entity(weapon).despawn_recursive();
+    }
+}
@@ -1,6 +1,10 @@
-use crate::components::ColorText;
+use crate::components::*;
 use crate::constants::*;
-use crate::GameState;
+use crate::events::SaveReplay;
+use crate::pause::PauseState;
+use crate::ships::{SelectedShip, ShipDefinition, ShipDefinitions};
+use crate::speed::GameSpeed;
+use crate::{Difficulty, GameMode, GameState};
 use bevy::prelude::*;
 
 #[derive(Resource)]
@@ -9,43 +13,61 @@ pub struct MenuData {
     pub button_entity: Entity,
 }
 
-pub fn setup_menu(mut commands: Commands) {
+pub fn spawn_button(parent: &mut ChildBuilder, label: &str, action: MenuButton) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    padding: UiRect {
+                        left: Val::Px(20.),
+                        right: Val::Px(20.),
+                        top: Val::Px(10.),
+                        bottom: Val::Px(10.),
+                    },
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font_size: 20.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+        });
+}
+
+pub fn setup_menu(mut commands: Commands, difficulty: Res<Difficulty>) {
     let button_entity = commands
         .spawn(NodeBundle {
             style: Style {
                 width: Val::Percent(100.),
                 height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
                 justify_content: JustifyContent::Center,
                 align_items: AlignItems::Center,
+                row_gap: Val::Px(10.),
                 ..default()
             },
             ..default()
         })
         .with_children(|parent| {
-            parent
-                .spawn(ButtonBundle {
-                    style: Style {
-                        padding: UiRect {
-                            left: Val::Px(20.),
-                            right: Val::Px(20.),
-                            top: Val::Px(10.),
-                            bottom: Val::Px(10.),
-                        },
-                        ..default()
-                    },
-                    background_color: NORMAL_BUTTON.into(),
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn(TextBundle::from_section(
-                        "Play",
-                        TextStyle {
-                            font_size: 20.,
-                            color: Color::rgb(0.9, 0.9, 0.9),
-                            ..default()
-                        },
-                    ));
-                });
+            spawn_button(parent, "Play", MenuButton::Play);
+            spawn_button(parent, "Practice", MenuButton::Practice);
+            spawn_button(
+                parent,
+                &format!("Difficulty: {:?}", *difficulty),
+                MenuButton::Difficulty,
+            );
+            spawn_button(parent, "High Scores", MenuButton::HighScores);
+            spawn_button(parent, "Settings", MenuButton::Settings);
         })
         .id();
 
@@ -93,18 +115,58 @@ pub fn cleanup_menu(mut commands: Commands, menu_data: Res<MenuData>) {
     commands.entity(menu_data.text_entity).despawn_recursive();
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn menu(
     mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut mode: ResMut<GameMode>,
+    mut difficulty: ResMut<Difficulty>,
+    mut speed: ResMut<GameSpeed>,
+    mut save_replay: EventWriter<SaveReplay>,
+    mut selected_ship: ResMut<SelectedShip>,
+    ships: Res<ShipDefinitions>,
+    definitions: Res<Assets<ShipDefinition>>,
     mut interaction_query: Query<
-        (&Interaction, &mut BackgroundColor),
+        (&Interaction, &mut BackgroundColor, &MenuButton),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
+    for (interaction, mut color, action) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 *color = PRESSED_BUTTON.into();
-                next_state.set(GameState::Running);
+                match action {
+                    MenuButton::Play => {
+                        *mode = GameMode::Normal;
+                        next_state.set(GameState::ShipSelect);
+                    }
+                    MenuButton::SelectShip(index) => {
+                        if let Some(definition) = ships
+                            .handles
+                            .get(*index)
+                            .and_then(|handle| definitions.get(handle))
+                        {
+                            selected_ship.definition = definition.clone();
+                        }
+                        next_state.set(GameState::Running);
+                    }
+                    MenuButton::Retry => next_state.set(GameState::Running),
+                    MenuButton::Difficulty => *difficulty = difficulty.next(),
+                    MenuButton::GameSpeed => speed.next(),
+                    MenuButton::HighScores => next_state.set(GameState::HighScores),
+                    MenuButton::Back | MenuButton::MainMenu => next_state.set(GameState::Menu),
+                    MenuButton::SaveReplay => save_replay.send(SaveReplay),
+                    MenuButton::Resume => next_pause_state.set(PauseState::Unpaused),
+                    MenuButton::Restart => next_state.set(GameState::Restarting),
+                    MenuButton::Settings
+                    | MenuButton::Setting(_)
+                    | MenuButton::CloseSettings
+                    | MenuButton::Practice
+                    | MenuButton::PracticeOption(_)
+                    | MenuButton::StartPractice
+                    | MenuButton::HighScoreMode
+                    | MenuButton::HighScoreDifficulty => {}
+                }
             }
             Interaction::Hovered => {
                 *color = HOVERED_BUTTON.into();
@@ -115,3 +177,53 @@ pub fn menu(
         }
     }
 }
+
+fn set_button_label(
+    target: MenuButton,
+    label: &str,
+    button_query: &Query<(&MenuButton, &Children)>,
+    text_query: &mut Query<&mut Text>,
+) {
+    for (action, children) in button_query.iter() {
+        if *action != target {
+            continue;
+        }
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = label.to_string();
+            }
+        }
+    }
+}
+
+pub fn update_difficulty_label(
+    difficulty: Res<Difficulty>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+    set_button_label(
+        MenuButton::Difficulty,
+        &format!("Difficulty: {:?}", *difficulty),
+        &button_query,
+        &mut text_query,
+    );
+}
+
+pub fn update_speed_label(
+    speed: Res<GameSpeed>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !speed.is_changed() {
+        return;
+    }
+    set_button_label(
+        MenuButton::GameSpeed,
+        &speed.label(),
+        &button_query,
+        &mut text_query,
+    );
+}
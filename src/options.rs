@@ -0,0 +1,131 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::ShotFired;
+use crate::input::{Actions, PlayerInput};
+use crate::player::{spawn_bullet, BulletAssets, PlayerStock};
+use bevy::prelude::*;
+
+#[derive(Component)]
+pub struct PlayerOption {
+    pub index: usize,
+    pub cooldown: f32,
+}
+
+fn option_count(stock: &PlayerStock) -> usize {
+    (stock.power_level() as usize).min(MAX_OPTIONS)
+}
+
+fn option_offset(index: usize, count: usize, focused: bool) -> Vec2 {
+    let step = index as f32 - (count as f32 - 1.) / 2.;
+    if focused {
+        Vec2::new(step * OPTION_FOCUS_SPACING, OPTION_FOCUS_OFFSET)
+    } else {
+        Vec2::new(
+            step * OPTION_SPACING,
+            OPTION_OFFSET - step.abs() * OPTION_SPACING / 2.,
+        )
+    }
+}
+
+pub fn spawn_option(commands: &mut Commands, index: usize, position: Vec3) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: OPTION_COLOR,
+                custom_size: Some(Vec2::splat(OPTION_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.truncate().extend(0.5)),
+            ..default()
+        },
+        PlayerOption {
+            index,
+            cooldown: 0.,
+        },
+    ));
+}
+
+pub fn spawn_options(
+    mut commands: Commands,
+    stock: Res<PlayerStock>,
+    ship_query: Query<&Transform, Added<Ship>>,
+) {
+    for ship in ship_query.iter() {
+        let count = option_count(&stock);
+        for index in 0..count {
+            let offset = option_offset(index, count, false);
+            spawn_option(&mut commands, index, ship.translation + offset.extend(0.));
+        }
+    }
+}
+
+pub fn sync_options(
+    mut commands: Commands,
+    stock: Res<PlayerStock>,
+    ship_query: Query<&Transform, With<Ship>>,
+    option_query: Query<(Entity, &PlayerOption)>,
+) {
+    let Ok(ship) = ship_query.get_single() else {
+        return;
+    };
+    let count = option_count(&stock);
+    let current = option_query.iter().len();
+
+    for (entity, option) in option_query.iter() {
+        if option.index >= count {
+            commands.entity(entity).despawn();
+        }
+    }
+    for index in current..count {
+        spawn_option(&mut commands, index, ship.translation);
+    }
+}
+
+pub fn move_options(
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    stock: Res<PlayerStock>,
+    ship_query: Query<&Transform, (With<Ship>, Without<PlayerOption>)>,
+    mut option_query: Query<(&mut Transform, &PlayerOption)>,
+) {
+    let Ok(ship) = ship_query.get_single() else {
+        return;
+    };
+    let count = option_count(&stock);
+    let focused = input.pressed(Actions::FOCUS);
+    let follow = (OPTION_FOLLOW_RATE * time.delta_seconds()).min(1.);
+
+    for (mut transform, option) in option_query.iter_mut() {
+        let target = ship.translation.truncate() + option_offset(option.index, count, focused);
+        let position = transform.translation.truncate();
+        transform.translation = position
+            .lerp(target, follow)
+            .extend(transform.translation.z);
+    }
+}
+
+pub fn fire_options(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    mut option_query: Query<(&Transform, &mut PlayerOption)>,
+    bullet_assets: Res<BulletAssets>,
+    mut shot_fired: EventWriter<ShotFired>,
+) {
+    for (transform, mut option) in option_query.iter_mut() {
+        option.cooldown = (option.cooldown - time.delta_seconds()).max(0.);
+        if !input.pressed(Actions::SHOOT) || option.cooldown > 0. {
+            continue;
+        }
+        option.cooldown = 1. / OPTION_FIRE_RATE;
+
+        spawn_bullet(
+            &mut commands,
+            &bullet_assets,
+            transform.translation + Vec3::new(0., OPTION_SIZE, 0.),
+            Vec2::new(0., BULLET_SPEED),
+            SHOT_DAMAGE,
+        );
+        shot_fired.send(ShotFired);
+    }
+}
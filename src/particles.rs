@@ -0,0 +1,250 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::*;
+use crate::settings::Settings;
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub enum EmitterMode {
+    Burst(u32),
+    Continuous { rate: f32, duration: f32 },
+}
+
+#[derive(Clone, Component, Debug)]
+pub struct ParticleEmitter {
+    pub mode: EmitterMode,
+    pub lifetime: f32,
+    pub speed: f32,
+    pub speed_variance: f32,
+    pub direction: f32,
+    pub spread: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+    pub elapsed: f32,
+    pub pending: f32,
+}
+
+impl ParticleEmitter {
+    pub fn burst(count: u32) -> Self {
+        Self {
+            mode: EmitterMode::Burst(count),
+            lifetime: 0.5,
+            speed: 80.,
+            speed_variance: 40.,
+            direction: 0.,
+            spread: 360.,
+            start_color: Color::WHITE,
+            end_color: Color::rgba(1., 1., 1., 0.),
+            start_size: 3.,
+            end_size: 1.,
+            elapsed: 0.,
+            pending: 0.,
+        }
+    }
+
+    pub fn with_lifetime(mut self, lifetime: f32) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32, variance: f32) -> Self {
+        self.speed = speed;
+        self.speed_variance = variance;
+        self
+    }
+
+    pub fn with_colors(mut self, start: Color, end: Color) -> Self {
+        self.start_color = start;
+        self.end_color = end;
+        self
+    }
+
+    pub fn with_sizes(mut self, start: f32, end: f32) -> Self {
+        self.start_size = start;
+        self.end_size = end;
+        self
+    }
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub age: f32,
+    pub lifetime: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+    pub start_size: f32,
+    pub end_size: f32,
+}
+
+// Particles are cosmetic, so they use their own generator and leave the
+// gameplay RNG (and therefore replays) untouched.
+#[derive(Resource)]
+pub struct ParticleRng(pub u64);
+
+impl Default for ParticleRng {
+    fn default() -> Self {
+        Self(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl ParticleRng {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next()
+    }
+}
+
+fn lerp_color(start: Color, end: Color, t: f32) -> Color {
+    let [r0, g0, b0, a0] = start.as_rgba_f32();
+    let [r1, g1, b1, a1] = end.as_rgba_f32();
+    Color::rgba(
+        r0 + (r1 - r0) * t,
+        g0 + (g1 - g0) * t,
+        b0 + (b1 - b0) * t,
+        a0 + (a1 - a0) * t,
+    )
+}
+
+pub fn spawn_emitter(commands: &mut Commands, emitter: ParticleEmitter, position: Vec2) {
+    commands.spawn((
+        TransformBundle::from_transform(Transform::from_translation(position.extend(PARTICLE_Z))),
+        emitter,
+    ));
+}
+
+pub fn emit_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut rng: ResMut<ParticleRng>,
+    mut emitter_query: Query<(Entity, &GlobalTransform, &mut ParticleEmitter)>,
+    particle_query: Query<(), With<Particle>>,
+) {
+    let mut budget = MAX_PARTICLES.saturating_sub(particle_query.iter().len());
+    for (entity, transform, mut emitter) in emitter_query.iter_mut() {
+        emitter.elapsed += time.delta_seconds();
+        let (count, finished) = match emitter.mode {
+            EmitterMode::Burst(count) => (count as usize, true),
+            EmitterMode::Continuous { rate, duration } => {
+                emitter.pending += rate * time.delta_seconds();
+                let count = emitter.pending.floor();
+                emitter.pending -= count;
+                (count as usize, emitter.elapsed >= duration)
+            }
+        };
+        if finished {
+            commands.entity(entity).despawn_recursive();
+        }
+        if !settings.particles {
+            continue;
+        }
+
+        let origin = transform.translation();
+        for _ in 0..count.min(budget) {
+            let angle = (emitter.direction + rng.range(-0.5, 0.5) * emitter.spread).to_radians();
+            let speed = emitter.speed + rng.range(-1., 1.) * emitter.speed_variance;
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: emitter.start_color,
+                        custom_size: Some(Vec2::splat(emitter.start_size)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(origin),
+                    ..default()
+                },
+                Particle {
+                    velocity: Vec2::from_angle(angle).rotate(Vec2::Y) * speed,
+                    age: 0.,
+                    lifetime: emitter.lifetime,
+                    start_color: emitter.start_color,
+                    end_color: emitter.end_color,
+                    start_size: emitter.start_size,
+                    end_size: emitter.end_size,
+                },
+            ));
+        }
+        budget = budget.saturating_sub(count);
+    }
+}
+
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Sprite, &mut Particle)>,
+) {
+    for (entity, mut transform, mut sprite, mut particle) in query.iter_mut() {
+        particle.age += time.delta_seconds();
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let t = particle.age / particle.lifetime;
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.);
+        sprite.color = lerp_color(particle.start_color, particle.end_color, t);
+        sprite.custom_size = Some(Vec2::splat(
+            particle.start_size + (particle.end_size - particle.start_size) * t,
+        ));
+    }
+}
+
+pub fn spawn_gameplay_particles(
+    mut commands: Commands,
+    mut destroyed: EventReader<EnemyDestroyed>,
+    mut grazes: EventReader<Graze>,
+    mut player_hits: EventReader<PlayerHit>,
+    ship_query: Query<Entity, With<Ship>>,
+) {
+    for event in destroyed.read() {
+        spawn_emitter(
+            &mut commands,
+            ParticleEmitter::burst(24)
+                .with_colors(Color::rgb(1., 0.8, 0.3), Color::rgba(1., 0.2, 0., 0.))
+                .with_sizes(4., 1.),
+            event.position.truncate(),
+        );
+    }
+    if grazes.read().count() > 0 {
+        if let Ok(ship) = ship_query.get_single() {
+            let sparks = commands
+                .spawn((
+                    TransformBundle::default(),
+                    ParticleEmitter {
+                        mode: EmitterMode::Continuous {
+                            rate: 60.,
+                            duration: 0.2,
+                        },
+                        ..ParticleEmitter::burst(0)
+                            .with_lifetime(0.25)
+                            .with_speed(120., 30.)
+                            .with_colors(Color::rgb(0.8, 0.9, 1.), Color::rgba(0.4, 0.6, 1., 0.))
+                            .with_sizes(2., 1.)
+                    },
+                ))
+                .id();
+            commands.entity(ship).add_child(sparks);
+        }
+    }
+    // The player is already gone by now on the final hit, so the burst goes
+    // where the event says it happened.
+    if let Some(hit) = player_hits.read().last() {
+        spawn_emitter(
+            &mut commands,
+            ParticleEmitter::burst(64)
+                .with_lifetime(1.)
+                .with_speed(140., 80.)
+                .with_colors(Color::rgb(0.6, 1., 0.6), Color::rgba(1., 1., 1., 0.))
+                .with_sizes(5., 1.),
+            hit.position.truncate(),
+        );
+    }
+}
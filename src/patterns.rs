@@ -0,0 +1,72 @@
+use crate::lasers::{spawn_curvy_laser, spawn_enemy_laser, CurvyLaserDefinition, LaserDefinition};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PatternAction {
+    Laser(LaserDefinition),
+    CurvyLaser(CurvyLaserDefinition),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PatternStep {
+    pub time: f32,
+    pub action: PatternAction,
+}
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct PatternScript {
+    pub steps: Vec<PatternStep>,
+    pub elapsed: f32,
+    pub next_step: usize,
+}
+
+impl PatternScript {
+    pub fn new(steps: Vec<PatternStep>) -> Self {
+        Self {
+            steps,
+            elapsed: 0.,
+            next_step: 0,
+        }
+    }
+}
+
+pub fn run_pattern_action(commands: &mut Commands, action: &PatternAction, origin: Vec2) {
+    match action {
+        PatternAction::Laser(definition) => {
+            spawn_enemy_laser(
+                commands,
+                *definition,
+                origin + Vec2::new(definition.x, definition.y),
+            );
+        }
+        PatternAction::CurvyLaser(definition) => {
+            spawn_curvy_laser(
+                commands,
+                *definition,
+                origin + Vec2::new(definition.x, definition.y),
+            );
+        }
+    }
+}
+
+pub fn run_patterns(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(&Transform, &mut PatternScript)>,
+) {
+    for (transform, mut script) in query.iter_mut() {
+        script.elapsed += time.delta_seconds();
+        while let Some(step) = script.steps.get(script.next_step) {
+            if step.time > script.elapsed {
+                break;
+            }
+            run_pattern_action(
+                &mut commands,
+                &step.action,
+                transform.translation.truncate(),
+            );
+            script.next_step += 1;
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::components::*;
+use crate::menu::spawn_button;
+use crate::GameState;
+use bevy::{prelude::*, window::WindowFocused};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, States)]
+pub enum PauseState {
+    #[default]
+    Unpaused,
+    Paused,
+    Settings,
+}
+
+#[derive(Component)]
+pub struct PauseMenu;
+
+pub fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    next_pause_state.set(match pause_state.get() {
+        PauseState::Unpaused => PauseState::Paused,
+        PauseState::Paused => PauseState::Unpaused,
+        PauseState::Settings => PauseState::Paused,
+    });
+}
+
+pub fn pause_on_focus_loss(
+    mut focus_events: EventReader<WindowFocused>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+    if lost_focus && *pause_state.get() == PauseState::Unpaused {
+        next_pause_state.set(PauseState::Paused);
+    }
+}
+
+pub fn reset_pause(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Unpaused);
+}
+
+pub fn restart_run(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Running);
+}
+
+pub fn setup_pause_menu(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            PauseMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 40.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+            spawn_button(parent, "Resume", MenuButton::Resume);
+            spawn_button(parent, "Restart", MenuButton::Restart);
+            spawn_button(parent, "Settings", MenuButton::Settings);
+            spawn_button(parent, "Quit to Menu", MenuButton::MainMenu);
+        });
+}
+
+pub fn cleanup_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,34 +1,89 @@
+use crate::combo::Combo;
 use crate::components::*;
 use crate::constants::*;
-use crate::{GameState, Score};
-use bevy::{
-    input::keyboard::KeyCode, prelude::*, sprite::collide_aabb::collide,
-    sprite::MaterialMesh2dBundle,
-};
+use crate::events::*;
+use crate::input::{Actions, PlayerInput};
+use crate::playfield::Playfield;
+use crate::practice::PracticeConfig;
+use crate::ships::{SelectedShip, ShipStats};
+use crate::weapons::{shot_pattern, spawn_missile, BombKind, Weapon, WeaponKind};
+use crate::{GameMode, GameState, Score};
+use bevy::{prelude::*, sprite::collide_aabb::collide, sprite::MaterialMesh2dBundle};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Resource, Serialize)]
+pub struct PlayerStock {
+    pub lives: u32,
+    pub bombs: u32,
+    pub power: u32,
+    pub life_fragments: u32,
+    pub bomb_fragments: u32,
+}
+
+impl Default for PlayerStock {
+    fn default() -> Self {
+        Self {
+            lives: STARTING_LIVES,
+            bombs: STARTING_BOMBS,
+            power: 0,
+            life_fragments: 0,
+            bomb_fragments: 0,
+        }
+    }
+}
+
+impl PlayerStock {
+    pub fn power_level(&self) -> u32 {
+        self.power / POWER_PER_LEVEL
+    }
+}
+
+pub fn reset_stock(
+    mut stock: ResMut<PlayerStock>,
+    mode: Res<GameMode>,
+    practice: Res<PracticeConfig>,
+) {
+    *stock = match *mode {
+        GameMode::Practice => PlayerStock {
+            lives: practice.lives,
+            bombs: practice.bombs,
+            power: practice.power_level * POWER_PER_LEVEL,
+            ..default()
+        },
+        _ => PlayerStock::default(),
+    };
+}
 
 pub fn spawn_player(
     mut commands: Commands,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     asset_server: Res<AssetServer>,
+    selected: Res<SelectedShip>,
+    playfield: Res<Playfield>,
 ) {
-    let ship_pos = Vec3::from((0., -(WINDOW_SIZE.y / 2.) + WINDOW_PADDING, 0.));
+    let definition = &selected.definition;
+    let ship_pos = Vec3::from((0., playfield.bottom() + WINDOW_PADDING, 0.));
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
-                custom_size: Some(SHIP_SIZE),
+                custom_size: Some(Vec2::splat(definition.size)),
                 ..default()
             },
-            texture: asset_server.load("ship.png"),
+            texture: asset_server.load(&definition.sprite),
             transform: Transform::from_translation(ship_pos),
             ..default()
         },
         Ship,
+        ShipStats::from(definition),
+        Weapon::new(definition.shot_type, definition.fire_rate),
     ));
     let player_pos = ship_pos + Vec3::from((0., -5., 1.));
     commands.spawn((
         MaterialMesh2dBundle {
-            mesh: meshes.add(shape::Circle::new(PLAYER_SIZE.x).into()).into(),
+            mesh: meshes
+                .add(shape::Circle::new(definition.hitbox_size).into())
+                .into(),
             material: materials.add(ColorMaterial::from(PLAYER_COLOR)),
             transform: Transform::from_translation(player_pos),
             ..default()
@@ -39,19 +94,24 @@ pub fn spawn_player(
 
 pub fn move_player(
     time: Res<Time>,
-    keyboard_input: Res<Input<KeyCode>>,
+    input: Res<PlayerInput>,
+    playfield: Res<Playfield>,
+    stats_query: Query<&ShipStats>,
     mut query: ParamSet<(
         Query<&mut Transform, With<Player>>,
         Query<&mut Transform, With<Ship>>,
     )>,
 ) {
-    let speed = if keyboard_input.pressed(KeyCode::ShiftLeft) {
-        PLAYER_FOCUS_SPEED
+    let Ok(stats) = stats_query.get_single() else {
+        return;
+    };
+    let speed = if input.pressed(Actions::FOCUS) {
+        stats.focus_speed
     } else {
-        PLAYER_SPEED
+        stats.speed
     };
 
-    if keyboard_input.pressed(KeyCode::A) || keyboard_input.pressed(KeyCode::Left) {
+    if input.pressed(Actions::LEFT) {
         for mut transform in query.p0().iter_mut() {
             transform.translation.x -= time.delta_seconds() * speed;
         }
@@ -60,7 +120,7 @@ pub fn move_player(
         }
     }
 
-    if keyboard_input.pressed(KeyCode::D) || keyboard_input.pressed(KeyCode::Right) {
+    if input.pressed(Actions::RIGHT) {
         for mut transform in query.p0().iter_mut() {
             transform.translation.x += time.delta_seconds() * speed;
         }
@@ -69,7 +129,7 @@ pub fn move_player(
         }
     }
 
-    if keyboard_input.pressed(KeyCode::W) || keyboard_input.pressed(KeyCode::Up) {
+    if input.pressed(Actions::UP) {
         for mut transform in query.p0().iter_mut() {
             transform.translation.y += time.delta_seconds() * speed;
         }
@@ -78,7 +138,7 @@ pub fn move_player(
         }
     }
 
-    if keyboard_input.pressed(KeyCode::S) || keyboard_input.pressed(KeyCode::Down) {
+    if input.pressed(Actions::DOWN) {
         for mut transform in query.p0().iter_mut() {
             transform.translation.y -= time.delta_seconds() * speed;
         }
@@ -88,103 +148,252 @@ pub fn move_player(
     }
 
     for mut transform in query.p0().iter_mut() {
-        transform.translation.x = transform.translation.x.clamp(
-            -WINDOW_SIZE.x / 2. + WINDOW_PADDING,
-            WINDOW_SIZE.x / 2. - WINDOW_PADDING,
-        );
-        transform.translation.y = transform.translation.y.clamp(
-            -WINDOW_SIZE.y / 2. + WINDOW_PADDING,
-            WINDOW_SIZE.y / 2. - WINDOW_PADDING,
-        );
+        let position = playfield.clamp(transform.translation.truncate(), WINDOW_PADDING);
+        transform.translation = position.extend(transform.translation.z);
     }
     for mut transform in query.p1().iter_mut() {
-        transform.translation.x = transform.translation.x.clamp(
-            -WINDOW_SIZE.x / 2. + WINDOW_PADDING,
-            WINDOW_SIZE.x / 2. - WINDOW_PADDING,
-        );
-        transform.translation.y = transform.translation.y.clamp(
-            -WINDOW_SIZE.y / 2. + WINDOW_PADDING,
-            WINDOW_SIZE.y / 2. - WINDOW_PADDING,
-        );
+        let position = playfield.clamp(transform.translation.truncate(), WINDOW_PADDING);
+        transform.translation = position.extend(transform.translation.z);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn collide_player(
-    mut next_state: ResMut<NextState<GameState>>,
     mut commands: Commands,
-    player_query: Query<(Entity, &Transform), With<Player>>,
-    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
-    graze_query: Query<&Transform, With<Ship>>,
+    player_query: Query<&Transform, (With<Player>, Without<Invulnerable>)>,
+    enemy_query: Query<(Entity, &Transform, Has<Grazed>), With<Enemy>>,
+    graze_query: Query<(&Transform, &ShipStats), With<Ship>>,
     mut score: ResMut<Score>,
-    keyboard_input: Res<Input<KeyCode>>,
+    combo: Res<Combo>,
+    input: Res<PlayerInput>,
+    mut player_hit: EventWriter<PlayerHit>,
+    mut graze: EventWriter<Graze>,
 ) {
-    for (player_entity, player_transform) in player_query.iter() {
-        for (enemy_entity, enemy_transform) in enemy_query.iter() {
-            let collision = collide(
-                player_transform.translation, // pos a
-                PLAYER_SIZE.truncate(),       // radius a
-                enemy_transform.translation,  // pos b
-                Vec2::from((7., 7.)),         // radius b
-            );
-            if collision.is_some() {
-                commands.entity(player_entity).despawn();
-                commands.entity(enemy_entity).despawn();
-                next_state.set(GameState::GameOver);
-            }
+    let Ok((_, stats)) = graze_query.get_single() else {
+        return;
+    };
+    for player_transform in player_query.iter() {
+        let hit = enemy_query.iter().any(|(_, enemy_transform, _)| {
+            collide(
+                player_transform.translation,   // pos a
+                Vec2::splat(stats.hitbox_size), // radius a
+                enemy_transform.translation,    // pos b
+                ENEMY_HITBOX,                   // radius b
+            )
+            .is_some()
+        });
+        if hit {
+            player_hit.send(PlayerHit {
+                position: player_transform.translation,
+            });
+            return;
         }
     }
-    for graze_transform in graze_query.iter() {
-        for (_, enemy_transform) in enemy_query.iter() {
+    for (graze_transform, stats) in graze_query.iter() {
+        for (enemy_entity, enemy_transform, grazed) in enemy_query.iter() {
             let collision = collide(
-                graze_transform.translation, // pos a
-                SHIP_SIZE,                   // radius a
-                enemy_transform.translation, // pos b
-                Vec2::from((7., 7.)),        // radius b
+                graze_transform.translation,   // pos a
+                Vec2::splat(stats.graze_size), // radius a
+                enemy_transform.translation,   // pos b
+                ENEMY_HITBOX,                  // radius b
             );
             if collision.is_some() {
-                score.value += {
-                    if keyboard_input.pressed(KeyCode::ShiftLeft) {
-                        10
-                    } else {
-                        1
-                    }
-                };
+                if !grazed {
+                    commands.entity(enemy_entity).insert(Grazed);
+                    graze.send(Graze);
+                }
+                score.value += graze_value(&combo, &input);
             }
         }
     }
 }
 
+pub fn graze_value(combo: &Combo, input: &PlayerInput) -> i32 {
+    combo.multiplier() * if input.pressed(Actions::FOCUS) { 10 } else { 1 }
+}
+
+#[derive(Default, Resource)]
+pub struct GodMode {
+    pub enabled: bool,
+}
+
+pub fn resolve_player_hit(
+    god: Res<GodMode>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+    mut stock: ResMut<PlayerStock>,
+    player_query: Query<Entity, (With<Player>, Without<Invulnerable>)>,
+    mut player_hit: EventReader<PlayerHit>,
+) {
+    if player_hit.read().count() == 0 || god.enabled {
+        return;
+    }
+    let Ok(player_entity) = player_query.get_single() else {
+        return;
+    };
+
+    if stock.lives == 0 {
+        commands.entity(player_entity).despawn();
+        // A demo never reaches the results screen, so it can't submit scores.
+        next_state.set(match state.get() {
+            GameState::Demo => GameState::Menu,
+            _ => GameState::GameOver,
+        });
+    } else {
+        stock.lives -= 1;
+        commands.entity(player_entity).insert(Invulnerable {
+            timer: HIT_INVULNERABILITY,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn use_bomb(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    mut stock: ResMut<PlayerStock>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    stats_query: Query<&ShipStats>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut bomb_used: EventWriter<BombUsed>,
+    mut damage_enemy: EventWriter<DamageEnemy>,
+) {
+    if !input.just_pressed(Actions::BOMB) || stock.bombs == 0 {
+        return;
+    }
+    let (Ok((player_entity, player_transform)), Ok(stats)) =
+        (player_query.get_single(), stats_query.get_single())
+    else {
+        return;
+    };
+
+    stock.bombs -= 1;
+    let (invulnerability, radius) = match stats.bomb {
+        BombKind::Screen => (BOMB_INVULNERABILITY, f32::INFINITY),
+        BombKind::Barrier => (BARRIER_INVULNERABILITY, BARRIER_RADIUS),
+    };
+    commands.entity(player_entity).insert(Invulnerable {
+        timer: invulnerability,
+    });
+    for (enemy_entity, enemy_transform) in enemy_query.iter() {
+        let distance = enemy_transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate());
+        if distance > radius {
+            continue;
+        }
+        damage_enemy.send(DamageEnemy {
+            target: enemy_entity,
+            amount: BOMB_DAMAGE,
+        });
+    }
+    bomb_used.send(BombUsed);
+}
+
+pub fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+) {
+    for (entity, mut invulnerable, mut visibility) in query.iter_mut() {
+        invulnerable.timer -= time.delta_seconds();
+        if invulnerable.timer <= 0. {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+        } else if (invulnerable.timer * 10.) as i32 % 2 == 0 {
+            *visibility = Visibility::Hidden;
+        } else {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
 pub fn fire_bullet(
     mut commands: Commands,
-    ship: Query<&Transform, With<Ship>>,
-    keyboard_input: Res<Input<KeyCode>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+    mut ship: Query<(&Transform, &mut Weapon), With<Ship>>,
+    input: Res<PlayerInput>,
+    stock: Res<PlayerStock>,
+    bullet_assets: Res<BulletAssets>,
+    mut shot_fired: EventWriter<ShotFired>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        for transform in ship.iter() {
-            let bullet_pos = transform.translation + Vec3::from((0., 20., 0.));
-            commands.spawn((
-                MaterialMesh2dBundle {
-                    mesh: meshes.add(shape::Circle::new(2.).into()).into(),
-                    material: materials.add(ColorMaterial::from(Color::WHITE)),
-                    transform: Transform::from_translation(bullet_pos),
-                    ..default()
-                },
-                Bullet,
-            ));
+    for (transform, mut weapon) in ship.iter_mut() {
+        weapon.cooldown = (weapon.cooldown - time.delta_seconds()).max(0.);
+        if weapon.kind == WeaponKind::Laser
+            || !input.pressed(Actions::SHOOT)
+            || weapon.cooldown > 0.
+        {
+            continue;
+        }
+        weapon.cooldown = 1. / weapon.fire_rate;
+
+        let focused = input.pressed(Actions::FOCUS);
+        for shot in shot_pattern(weapon.kind, stock.power_level(), focused) {
+            let position = transform.translation + shot.offset.extend(0.);
+            if weapon.kind == WeaponKind::Homing {
+                spawn_missile(&mut commands, position, shot.velocity, shot.damage);
+            } else {
+                spawn_bullet(
+                    &mut commands,
+                    &bullet_assets,
+                    position,
+                    shot.velocity,
+                    shot.damage,
+                );
+            }
+            shot_fired.send(ShotFired);
         }
     }
 }
 
+// Every bullet shares one mesh and material rather than adding new assets
+// for each shot.
+#[derive(Resource)]
+pub struct BulletAssets {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<ColorMaterial>,
+}
+
+pub fn load_bullet_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(BulletAssets {
+        mesh: meshes.add(shape::Circle::new(2.).into()),
+        material: materials.add(ColorMaterial::from(Color::WHITE)),
+    });
+}
+
+pub fn spawn_bullet(
+    commands: &mut Commands,
+    assets: &BulletAssets,
+    position: Vec3,
+    velocity: Vec2,
+    damage: i32,
+) {
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone().into(),
+            material: assets.material.clone(),
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        Bullet { velocity, damage },
+    ));
+}
+
 pub fn move_bullets(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform), With<Bullet>>,
+    mut query: Query<(Entity, &mut Transform, &Bullet)>,
     time: Res<Time>,
+    playfield: Res<Playfield>,
 ) {
-    for (entity, mut transform) in query.iter_mut() {
-        transform.translation.y += time.delta_seconds() * 300.;
-        if transform.translation.y > WINDOW_SIZE.y / 2. {
+    for (entity, mut transform, bullet) in query.iter_mut() {
+        transform.translation += (bullet.velocity * time.delta_seconds()).extend(0.);
+        if !playfield.contains(transform.translation.truncate(), 0.) {
             commands.entity(entity).despawn();
         }
     }
@@ -192,22 +401,27 @@ pub fn move_bullets(
 
 pub fn collide_bullets(
     mut commands: Commands,
-    mut score: ResMut<Score>,
-    bullet_query: Query<(Entity, &Transform), With<Bullet>>,
+    bullet_query: Query<(Entity, &Transform, &Bullet)>,
     enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut bullet_hit: EventWriter<BulletHit>,
+    mut damage_enemy: EventWriter<DamageEnemy>,
 ) {
-    for (bullet_entity, bullet_transform) in bullet_query.iter() {
+    for (bullet_entity, bullet_transform, bullet) in bullet_query.iter() {
         for (enemy_entity, enemy_transform) in enemy_query.iter() {
             let collision = collide(
                 bullet_transform.translation, // pos a
-                Vec2::from((2., 2.)),         // radius a
+                BULLET_HITBOX,                // radius a
                 enemy_transform.translation,  // pos b
-                Vec2::from((20., 20.)),       // radius b
+                ENEMY_SHOT_HITBOX,            // radius b
             );
             if collision.is_some() {
                 commands.entity(bullet_entity).despawn();
-                commands.entity(enemy_entity).despawn();
-                score.value += 10;
+                bullet_hit.send(BulletHit);
+                damage_enemy.send(DamageEnemy {
+                    target: enemy_entity,
+                    amount: bullet.damage,
+                });
+                break;
             }
         }
     }
@@ -0,0 +1,186 @@
+use crate::components::*;
+use crate::constants::*;
+use bevy::{prelude::*, render::camera::ScalingMode, sprite::Anchor};
+
+#[derive(Resource)]
+pub struct Playfield {
+    pub rect: Rect,
+}
+
+impl Default for Playfield {
+    fn default() -> Self {
+        Self {
+            rect: Rect::from_center_size(Vec2::ZERO, PLAYFIELD_SIZE),
+        }
+    }
+}
+
+impl Playfield {
+    pub fn top(&self) -> f32 {
+        self.rect.max.y
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.rect.min.y
+    }
+
+    pub fn contains(&self, position: Vec2, margin: f32) -> bool {
+        self.rect.inset(margin).contains(position)
+    }
+
+    pub fn clamp(&self, position: Vec2, padding: f32) -> Vec2 {
+        let inner = self.rect.inset(-padding);
+        position.clamp(inner.min, inner.max)
+    }
+
+    pub fn screen(&self) -> Rect {
+        Rect::new(
+            self.rect.min.x,
+            self.rect.min.y,
+            self.rect.max.x + SIDE_PANEL_WIDTH,
+            self.rect.max.y,
+        )
+    }
+
+    pub fn panel(&self) -> Rect {
+        Rect::new(
+            self.rect.max.x,
+            self.rect.min.y,
+            self.rect.max.x + SIDE_PANEL_WIDTH,
+            self.rect.max.y,
+        )
+    }
+}
+
+pub fn setup_camera(mut commands: Commands, playfield: Res<Playfield>) {
+    let screen = playfield.screen();
+    let mut camera = Camera2dBundle::default();
+    camera.transform.translation = screen.center().extend(camera.transform.translation.z);
+    camera.projection.scaling_mode = ScalingMode::AutoMin {
+        min_width: screen.width(),
+        min_height: screen.height(),
+    };
+    commands.spawn((camera, MainCamera));
+}
+
+pub fn setup_playfield_frame(mut commands: Commands, playfield: Res<Playfield>) {
+    let screen = playfield.screen();
+    let panel = playfield.panel();
+
+    // Bars extend well past the logical screen so any window aspect ratio is
+    // covered by the letterbox or pillarbox.
+    let extent = screen.size().max_element() * 4.;
+    let bars = [
+        Rect::new(
+            screen.min.x - extent,
+            screen.min.y - extent,
+            screen.min.x,
+            screen.max.y + extent,
+        ),
+        Rect::new(
+            screen.max.x,
+            screen.min.y - extent,
+            screen.max.x + extent,
+            screen.max.y + extent,
+        ),
+        Rect::new(
+            screen.min.x,
+            screen.max.y,
+            screen.max.x,
+            screen.max.y + extent,
+        ),
+        Rect::new(
+            screen.min.x,
+            screen.min.y - extent,
+            screen.max.x,
+            screen.min.y,
+        ),
+    ];
+    for bar in bars {
+        commands.spawn(SpriteBundle {
+            sprite: Sprite {
+                color: LETTERBOX_COLOR,
+                custom_size: Some(bar.size()),
+                ..default()
+            },
+            transform: Transform::from_translation(bar.center().extend(FRAME_Z)),
+            ..default()
+        });
+    }
+
+    commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: SIDE_PANEL_COLOR,
+            custom_size: Some(panel.size()),
+            ..default()
+        },
+        transform: Transform::from_translation(panel.center().extend(FRAME_Z)),
+        ..default()
+    });
+}
+
+pub fn spawn_hud(mut commands: Commands, playfield: Res<Playfield>) {
+    let panel = playfield.panel();
+    let origin = Vec2::new(
+        panel.min.x + WINDOW_PADDING / 2.,
+        panel.max.y - WINDOW_PADDING,
+    );
+    let style = TextStyle {
+        font_size: 14.,
+        color: Color::rgb(0.9, 0.9, 0.9),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(origin.extend(FRAME_Z + 1.))),
+            Hud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section("", style.clone()),
+                    text_anchor: Anchor::TopLeft,
+                    ..default()
+                },
+                ScoreText,
+            ));
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            color: Color::rgb(0.9, 0.8, 0.3),
+                            ..style.clone()
+                        },
+                    ),
+                    text_anchor: Anchor::TopLeft,
+                    transform: Transform::from_xyz(0., -20., 0.),
+                    ..default()
+                },
+                ComboText,
+            ));
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::rgb(0.9, 0.8, 0.3),
+                        custom_size: Some(Vec2::new(0., 3.)),
+                        anchor: Anchor::TopLeft,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0., -38., 0.),
+                    ..default()
+                },
+                ComboBar,
+            ));
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section("", style),
+                    text_anchor: Anchor::TopLeft,
+                    transform: Transform::from_xyz(0., -50., 0.),
+                    ..default()
+                },
+                StockText,
+            ));
+        });
+}
@@ -0,0 +1,194 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::menu::spawn_button;
+use crate::speed::GameSpeed;
+use crate::stage::{StageDefinition, StageDefinitions};
+use crate::{GameMode, GameState};
+use bevy::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PracticeOption {
+    Stage,
+    Section,
+    Lives,
+    Bombs,
+    Power,
+}
+
+impl PracticeOption {
+    pub const ALL: [PracticeOption; 5] = [
+        PracticeOption::Stage,
+        PracticeOption::Section,
+        PracticeOption::Lives,
+        PracticeOption::Bombs,
+        PracticeOption::Power,
+    ];
+}
+
+#[derive(Resource)]
+pub struct PracticeConfig {
+    pub stage: usize,
+    pub section: usize,
+    pub lives: u32,
+    pub bombs: u32,
+    pub power_level: u32,
+}
+
+impl Default for PracticeConfig {
+    fn default() -> Self {
+        Self {
+            stage: 0,
+            section: 0,
+            lives: STARTING_LIVES,
+            bombs: STARTING_BOMBS,
+            power_level: 0,
+        }
+    }
+}
+
+impl PracticeConfig {
+    pub fn cycle(
+        &mut self,
+        option: PracticeOption,
+        stages: &StageDefinitions,
+        definitions: &Assets<StageDefinition>,
+    ) {
+        match option {
+            PracticeOption::Stage => {
+                self.stage = (self.stage + 1) % stages.handles.len().max(1);
+                self.section = 0;
+            }
+            PracticeOption::Section => {
+                let count = stages
+                    .get(self.stage, definitions)
+                    .map_or(0, |stage| stage.sections.len());
+                self.section = (self.section + 1) % count.max(1);
+            }
+            PracticeOption::Lives => self.lives = (self.lives + 1) % (PRACTICE_MAX_LIVES + 1),
+            PracticeOption::Bombs => self.bombs = (self.bombs + 1) % (PRACTICE_MAX_BOMBS + 1),
+            PracticeOption::Power => {
+                self.power_level = (self.power_level + 1) % (MAX_POWER / POWER_PER_LEVEL + 1)
+            }
+        }
+    }
+
+    pub fn label(&self, option: PracticeOption, stage: Option<&StageDefinition>) -> String {
+        match option {
+            PracticeOption::Stage => {
+                format!("Stage: {}", stage.map_or("-", |stage| stage.name.as_str()))
+            }
+            PracticeOption::Section => format!(
+                "Section: {}",
+                stage
+                    .and_then(|stage| stage.sections.get(self.section))
+                    .map_or("Start", |section| section.name.as_str())
+            ),
+            PracticeOption::Lives => format!("Lives: {}", self.lives),
+            PracticeOption::Bombs => format!("Bombs: {}", self.bombs),
+            PracticeOption::Power => format!("Power: {}", self.power_level),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct PracticeMenu;
+
+pub fn setup_practice_menu(
+    mut commands: Commands,
+    config: Res<PracticeConfig>,
+    stages: Res<StageDefinitions>,
+    definitions: Res<Assets<StageDefinition>>,
+    speed: Res<GameSpeed>,
+) {
+    let stage = stages.get(config.stage, &definitions);
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.),
+                    padding: UiRect::all(Val::Px(WINDOW_PADDING)),
+                    ..default()
+                },
+                ..default()
+            },
+            PracticeMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Practice",
+                TextStyle {
+                    font_size: 24.,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                    ..default()
+                },
+            ));
+            for option in PracticeOption::ALL {
+                spawn_button(
+                    parent,
+                    &config.label(option, stage),
+                    MenuButton::PracticeOption(option),
+                );
+            }
+            spawn_button(parent, &speed.label(), MenuButton::GameSpeed);
+            spawn_button(parent, "Start", MenuButton::StartPractice);
+            spawn_button(parent, "Back", MenuButton::Back);
+        });
+}
+
+pub fn cleanup_practice_menu(mut commands: Commands, query: Query<Entity, With<PracticeMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn practice_menu(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut mode: ResMut<GameMode>,
+    mut config: ResMut<PracticeConfig>,
+    stages: Res<StageDefinitions>,
+    definitions: Res<Assets<StageDefinition>>,
+    interaction_query: Query<(&Interaction, &MenuButton), (Changed<Interaction>, With<Button>)>,
+) {
+    for (interaction, action) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            MenuButton::Practice => next_state.set(GameState::PracticeSelect),
+            MenuButton::PracticeOption(option) => config.cycle(*option, &stages, &definitions),
+            MenuButton::StartPractice => {
+                *mode = GameMode::Practice;
+                next_state.set(GameState::ShipSelect);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn update_practice_labels(
+    config: Res<PracticeConfig>,
+    stages: Res<StageDefinitions>,
+    definitions: Res<Assets<StageDefinition>>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !config.is_changed() {
+        return;
+    }
+    let stage = stages.get(config.stage, &definitions);
+    for (action, children) in button_query.iter() {
+        let MenuButton::PracticeOption(option) = action else {
+            continue;
+        };
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = config.label(*option, stage);
+            }
+        }
+    }
+}
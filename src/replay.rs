@@ -0,0 +1,589 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::SaveReplay;
+use crate::highscores::NameEntry;
+use crate::input::{Actions, PlayerInput};
+use crate::ships::{SelectedShip, ShipDefinition};
+use crate::storage::{data_path, save_bytes};
+use crate::{Difficulty, GameMode, RunFlags, RunSeed, Score};
+use bevy::prelude::*;
+use bevy_prng::ChaCha8Rng;
+use bevy_rand::prelude::*;
+use rand_core::RngCore;
+use serde::Deserialize;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const REPLAY_MAGIC: &[u8; 4] = b"DGRP";
+// Version 1 was the original RON format; every binary replay is version 2+.
+pub const REPLAY_VERSION: u16 = 2;
+// An hour of play; anything longer is treated as a corrupt file.
+const MAX_REPLAY_TICKS: usize = 64 * 60 * 60;
+const FLAG_PRACTICE: u8 = 1;
+const FLAG_CHEATED: u8 = 2;
+const FLAG_ASSISTED: u8 = 4;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub game_version: String,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub ship: String,
+    pub date: u64,
+    pub name: String,
+    pub score: i32,
+    pub hash_interval: u32,
+    pub practice: bool,
+    pub cheated: bool,
+    pub assisted: bool,
+    pub inputs: Vec<u8>,
+    pub hashes: Vec<u64>,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: 0,
+            difficulty: Difficulty::default(),
+            ship: String::new(),
+            date: 0,
+            name: String::new(),
+            score: 0,
+            hash_interval: REPLAY_HASH_INTERVAL,
+            practice: false,
+            cheated: false,
+            assisted: false,
+            inputs: Vec::new(),
+            hashes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ReplayV1 {
+    seed: u64,
+    difficulty: Difficulty,
+    // Replays from before ship select have no ship.
+    #[serde(default)]
+    ship: String,
+    score: i32,
+    inputs: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReplayError {
+    Truncated,
+    UnsupportedVersion(u16),
+    InvalidDifficulty(u8),
+    InvalidText,
+    TooLong,
+    Legacy(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Truncated => write!(f, "replay data ends early"),
+            ReplayError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "replay format version {} is newer than this game",
+                    version
+                )
+            }
+            ReplayError::InvalidDifficulty(value) => write!(f, "unknown difficulty {}", value),
+            ReplayError::InvalidText => write!(f, "replay text is not valid UTF-8"),
+            ReplayError::TooLong => write!(f, "replay is longer than any real run"),
+            ReplayError::Legacy(err) => write!(f, "could not read RON replay: {}", err),
+        }
+    }
+}
+
+fn difficulty_index(difficulty: Difficulty) -> u8 {
+    match difficulty {
+        Difficulty::Easy => 0,
+        Difficulty::Normal => 1,
+        Difficulty::Hard => 2,
+    }
+}
+
+fn difficulty_from_index(index: u8) -> Result<Difficulty, ReplayError> {
+    match index {
+        0 => Ok(Difficulty::Easy),
+        1 => Ok(Difficulty::Normal),
+        2 => Ok(Difficulty::Hard),
+        _ => Err(ReplayError::InvalidDifficulty(index)),
+    }
+}
+
+fn put_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn put_str(bytes: &mut Vec<u8>, value: &str) {
+    put_varint(bytes, value.len() as u64);
+    bytes.extend_from_slice(value.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ReplayError> {
+        if self.bytes.len() < count {
+            return Err(ReplayError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(count);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, ReplayError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, ReplayError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ReplayError::Truncated)
+    }
+
+    fn string(&mut self) -> Result<String, ReplayError> {
+        let length = self.varint()? as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| ReplayError::InvalidText)
+    }
+}
+
+// Layout: magic, version (u16), header length (u32), header, input runs,
+// state hashes. Fields added to the header later go at its end, and readers
+// skip whatever header bytes they don't know about.
+impl Replay {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = Vec::new();
+        put_str(&mut header, &self.game_version);
+        header.extend_from_slice(&self.seed.to_le_bytes());
+        header.push(difficulty_index(self.difficulty));
+        put_str(&mut header, &self.ship);
+        header.extend_from_slice(&self.date.to_le_bytes());
+        put_str(&mut header, &self.name);
+        header.extend_from_slice(&self.score.to_le_bytes());
+        header.extend_from_slice(&self.hash_interval.to_le_bytes());
+        header.push(
+            if self.practice { FLAG_PRACTICE } else { 0 }
+                | if self.cheated { FLAG_CHEATED } else { 0 }
+                | if self.assisted { FLAG_ASSISTED } else { 0 },
+        );
+
+        let mut bytes = REPLAY_MAGIC.to_vec();
+        bytes.extend_from_slice(&REPLAY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&header);
+
+        let runs = encode_runs(&self.inputs);
+        put_varint(&mut bytes, runs.len() as u64);
+        for (actions, length) in runs {
+            bytes.push(actions);
+            put_varint(&mut bytes, length as u64);
+        }
+        put_varint(&mut bytes, self.hashes.len() as u64);
+        for hash in &self.hashes {
+            bytes.extend_from_slice(&hash.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        if !bytes.starts_with(REPLAY_MAGIC) {
+            return Self::from_ron_v1(bytes);
+        }
+        let mut reader = Reader {
+            bytes: &bytes[REPLAY_MAGIC.len()..],
+        };
+        match u16::from_le_bytes(reader.array()?) {
+            2 => Self::read_v2(&mut reader),
+            version => Err(ReplayError::UnsupportedVersion(version)),
+        }
+    }
+
+    fn from_ron_v1(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let text = std::str::from_utf8(bytes).map_err(|_| ReplayError::InvalidText)?;
+        let legacy: ReplayV1 =
+            ron::from_str(text).map_err(|err| ReplayError::Legacy(err.to_string()))?;
+        Ok(Self {
+            game_version: String::new(),
+            seed: legacy.seed,
+            difficulty: legacy.difficulty,
+            ship: if legacy.ship.is_empty() {
+                ShipDefinition::default().id
+            } else {
+                legacy.ship
+            },
+            score: legacy.score,
+            inputs: legacy.inputs,
+            ..default()
+        })
+    }
+
+    fn read_v2(reader: &mut Reader) -> Result<Self, ReplayError> {
+        let header_length = u32::from_le_bytes(reader.array()?) as usize;
+        let mut header = Reader {
+            bytes: reader.take(header_length)?,
+        };
+        let mut replay = Self {
+            game_version: header.string()?,
+            seed: u64::from_le_bytes(header.array()?),
+            difficulty: difficulty_from_index(header.u8()?)?,
+            ship: header.string()?,
+            date: u64::from_le_bytes(header.array()?),
+            name: header.string()?,
+            score: i32::from_le_bytes(header.array()?),
+            hash_interval: u32::from_le_bytes(header.array()?),
+            ..default()
+        };
+        // Replays saved before the flags byte existed end here.
+        if !header.bytes.is_empty() {
+            let flags = header.u8()?;
+            replay.practice = flags & FLAG_PRACTICE != 0;
+            replay.cheated = flags & FLAG_CHEATED != 0;
+            replay.assisted = flags & FLAG_ASSISTED != 0;
+        }
+
+        for _ in 0..reader.varint()? {
+            let actions = reader.u8()?;
+            let length = reader.varint()? as usize;
+            if length > MAX_REPLAY_TICKS - replay.inputs.len() {
+                return Err(ReplayError::TooLong);
+            }
+            replay.inputs.resize(replay.inputs.len() + length, actions);
+        }
+        for _ in 0..reader.varint()? {
+            replay.hashes.push(u64::from_le_bytes(reader.array()?));
+        }
+        Ok(replay)
+    }
+
+    // Index of the first tick whose state hash differs from `other`.
+    pub fn first_divergence(&self, other: &Replay) -> Option<usize> {
+        let interval = self.hash_interval as usize;
+        self.hashes
+            .iter()
+            .zip(&other.hashes)
+            .position(|(a, b)| a != b)
+            .map(|index| (index + 1) * interval)
+    }
+}
+
+fn encode_runs(inputs: &[u8]) -> Vec<(u8, usize)> {
+    let mut runs: Vec<(u8, usize)> = Vec::new();
+    for &actions in inputs {
+        match runs.last_mut() {
+            Some((last, length)) if *last == actions => *length += 1,
+            _ => runs.push((actions, 1)),
+        }
+    }
+    runs
+}
+
+struct StateHasher(u64);
+
+impl StateHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_vec(&mut self, value: Vec3) {
+        for component in value.to_array() {
+            self.write(&component.to_bits().to_le_bytes());
+        }
+    }
+}
+
+// Entities are folded in with a wrapping sum so the hash doesn't depend on
+// query iteration order.
+pub fn record_state_hash(
+    mut recorder: ResMut<ReplayRecorder>,
+    score: Res<Score>,
+    rng: Res<GlobalEntropy<ChaCha8Rng>>,
+    ship_query: Query<&Transform, With<Ship>>,
+    enemy_query: Query<(&Transform, &Enemy)>,
+    bullet_query: Query<&Transform, With<Bullet>>,
+) {
+    let replay = &mut recorder.replay;
+    if replay.hash_interval == 0 || replay.inputs.len() % replay.hash_interval as usize != 0 {
+        return;
+    }
+
+    let mut hasher = StateHasher::new();
+    hasher.write(&score.value.to_le_bytes());
+    hasher.write(&rng.clone().next_u64().to_le_bytes());
+    for transform in ship_query.iter() {
+        hasher.write_vec(transform.translation);
+    }
+    let mut entities = 0u64;
+    for (transform, enemy) in enemy_query.iter() {
+        let mut entity = StateHasher::new();
+        entity.write_vec(transform.translation);
+        entity.write(&enemy.health.to_le_bytes());
+        entities = entities.wrapping_add(entity.0);
+    }
+    for transform in bullet_query.iter() {
+        let mut entity = StateHasher::new();
+        entity.write_vec(transform.translation);
+        entities = entities.wrapping_add(entity.0);
+    }
+    hasher.write(&entities.to_le_bytes());
+    replay.hashes.push(hasher.0);
+}
+
+#[derive(Default, Resource)]
+pub struct ReplayRecorder {
+    pub replay: Replay,
+}
+
+pub fn start_recording(
+    mut recorder: ResMut<ReplayRecorder>,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    selected_ship: Res<SelectedShip>,
+    mode: Res<GameMode>,
+) {
+    recorder.replay = Replay {
+        seed: seed.value,
+        difficulty: *difficulty,
+        ship: selected_ship.definition.id.clone(),
+        practice: *mode == GameMode::Practice,
+        ..default()
+    };
+}
+
+pub fn record_input(mut recorder: ResMut<ReplayRecorder>, input: Res<PlayerInput>) {
+    recorder.replay.inputs.push(input.current.0);
+}
+
+// Present while a run is driven by a replay instead of the keyboard; the run
+// takes its seed from it.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub tick: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, tick: 0 }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.replay.inputs.len()
+    }
+}
+
+pub fn play_replay_input(playback: Option<ResMut<ReplayPlayback>>, mut input: ResMut<PlayerInput>) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    let actions = playback
+        .replay
+        .inputs
+        .get(playback.tick)
+        .copied()
+        .unwrap_or_default();
+    input.push(Actions(actions));
+    playback.tick += 1;
+}
+
+pub fn save_replay(
+    mut events: EventReader<SaveReplay>,
+    mut recorder: ResMut<ReplayRecorder>,
+    score: Res<Score>,
+    name_entry: Option<Res<NameEntry>>,
+    flags: Res<RunFlags>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if events.read().count() == 0 {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    recorder.replay.score = score.value;
+    recorder.replay.date = timestamp;
+    recorder.replay.name = name_entry.map(|entry| entry.name()).unwrap_or_default();
+    // Console commands can mark the run as cheated after it starts.
+    recorder.replay.cheated = flags.cheated;
+    recorder.replay.assisted = flags.assisted;
+    save_bytes(
+        data_path(&format!("replays/{}.dreplay", timestamp)),
+        &recorder.replay.to_bytes(),
+    );
+
+    for (action, children) in button_query.iter() {
+        if *action != MenuButton::SaveReplay {
+            continue;
+        }
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = "Saved".to_string();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Replay {
+        let mut inputs = vec![0; 100];
+        inputs.extend([32; 500]);
+        inputs.extend([36, 40, 36, 40]);
+        Replay {
+            seed: 0x1234_5678_9abc_def0,
+            difficulty: Difficulty::Hard,
+            ship: "needle".to_string(),
+            date: 1_700_000_000,
+            name: "ABC".to_string(),
+            score: 123_456,
+            cheated: true,
+            inputs,
+            hashes: vec![1, u64::MAX, 0xdead_beef],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let replay = sample();
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()), Ok(replay));
+    }
+
+    #[test]
+    fn empty_round_trip() {
+        let replay = Replay::default();
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()), Ok(replay));
+    }
+
+    #[test]
+    fn inputs_are_run_length_encoded() {
+        let replay = sample();
+        let empty = Replay {
+            inputs: Vec::new(),
+            ..replay.clone()
+        };
+        assert!(replay.to_bytes().len() - empty.to_bytes().len() < 20);
+    }
+
+    #[test]
+    fn migrates_ron_v1() {
+        let ron = r#"(seed: 42, difficulty: Easy, ship: "arrow", score: 900, inputs: [0, 32, 32])"#;
+        let replay = Replay::from_bytes(ron.as_bytes()).unwrap();
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.difficulty, Difficulty::Easy);
+        assert_eq!(replay.ship, "arrow");
+        assert_eq!(replay.score, 900);
+        assert_eq!(replay.inputs, vec![0, 32, 32]);
+        assert!(replay.hashes.is_empty());
+
+        let migrated = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(migrated, replay);
+    }
+
+    #[test]
+    fn migrates_ron_v1_without_ship() {
+        let ron = r#"(seed: 7, difficulty: Hard, score: 50, inputs: [1, 2])"#;
+        let replay = Replay::from_bytes(ron.as_bytes()).unwrap();
+        assert_eq!(replay.ship, ShipDefinition::default().id);
+        assert_eq!(replay.difficulty, Difficulty::Hard);
+        assert_eq!(replay.inputs, vec![1, 2]);
+    }
+
+    #[test]
+    fn skips_unknown_header_fields() {
+        let replay = sample();
+        let mut bytes = replay.to_bytes();
+        let header_start = REPLAY_MAGIC.len() + 6;
+        let header_length =
+            u32::from_le_bytes(bytes[header_start - 4..header_start].try_into().unwrap());
+        let header_end = header_start + header_length as usize;
+        bytes.splice(header_end..header_end, [7, 7, 7]);
+        bytes[header_start - 4..header_start].copy_from_slice(&(header_length + 3).to_le_bytes());
+        assert_eq!(Replay::from_bytes(&bytes), Ok(replay));
+    }
+
+    #[test]
+    fn reads_headers_without_flags() {
+        let replay = Replay {
+            cheated: false,
+            ..sample()
+        };
+        let mut bytes = Replay {
+            practice: true,
+            assisted: true,
+            ..replay.clone()
+        }
+        .to_bytes();
+        let header_start = REPLAY_MAGIC.len() + 6;
+        let header_length =
+            u32::from_le_bytes(bytes[header_start - 4..header_start].try_into().unwrap());
+        bytes.remove(header_start + header_length as usize - 1);
+        bytes[header_start - 4..header_start].copy_from_slice(&(header_length - 1).to_le_bytes());
+        assert_eq!(Replay::from_bytes(&bytes), Ok(replay));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let mut bytes = sample().to_bytes();
+        bytes[4..6].copy_from_slice(&(REPLAY_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::UnsupportedVersion(REPLAY_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = sample().to_bytes();
+        assert_eq!(
+            Replay::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ReplayError::Truncated)
+        );
+    }
+
+    #[test]
+    fn finds_first_divergence() {
+        let replay = sample();
+        let mut other = replay.clone();
+        assert_eq!(replay.first_divergence(&other), None);
+        other.hashes[1] = 0;
+        assert_eq!(
+            replay.first_divergence(&other),
+            Some(2 * REPLAY_HASH_INTERVAL as usize)
+        );
+    }
+}
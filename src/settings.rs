@@ -0,0 +1,305 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::menu::spawn_button;
+use crate::pause::PauseState;
+use crate::storage::{config_path, load_ron, save_ron};
+use crate::GameState;
+use bevy::{
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowTheme},
+};
+use serde::{Deserialize, Serialize};
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SettingsOption {
+    VSync,
+    WindowScale,
+    Fullscreen,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    ScreenShake,
+    Hitstop,
+    Hitboxes,
+    Particles,
+}
+
+impl SettingsOption {
+    pub const ALL: [SettingsOption; 10] = [
+        SettingsOption::VSync,
+        SettingsOption::WindowScale,
+        SettingsOption::Fullscreen,
+        SettingsOption::MasterVolume,
+        SettingsOption::MusicVolume,
+        SettingsOption::SfxVolume,
+        SettingsOption::ScreenShake,
+        SettingsOption::Hitstop,
+        SettingsOption::Hitboxes,
+        SettingsOption::Particles,
+    ];
+}
+
+#[derive(Clone, Debug, Deserialize, Resource, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub vsync: bool,
+    pub window_scale: f32,
+    pub fullscreen: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub screen_shake: f32,
+    pub hitstop: f32,
+    pub show_hitbox: bool,
+    pub particles: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            vsync: true,
+            window_scale: 1.,
+            fullscreen: false,
+            master_volume: 1.,
+            music_volume: 0.8,
+            sfx_volume: 0.8,
+            screen_shake: 1.,
+            hitstop: 1.,
+            show_hitbox: true,
+            particles: true,
+        }
+    }
+}
+
+fn cycle(value: f32, step: f32, max: f32) -> f32 {
+    let next = ((value + step) / step).round() * step;
+    if next > max + f32::EPSILON {
+        0.
+    } else {
+        next
+    }
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        load_ron(config_path(SETTINGS_FILE)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        save_ron(config_path(SETTINGS_FILE), self);
+    }
+
+    pub fn window(&self) -> Window {
+        let mut window = Window {
+            fit_canvas_to_parent: true,
+            prevent_default_event_handling: false,
+            resizable: true,
+            title: "Dodge".to_string(),
+            window_theme: Some(WindowTheme::Dark),
+            ..default()
+        };
+        self.apply_window(&mut window);
+        window
+    }
+
+    pub fn apply_window(&self, window: &mut Window) {
+        window.present_mode = if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        window.mode = if self.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+        let size = WINDOW_SIZE * self.window_scale;
+        window.resolution.set(size.x, size.y);
+    }
+
+    pub fn cycle(&mut self, option: SettingsOption) {
+        match option {
+            SettingsOption::VSync => self.vsync = !self.vsync,
+            SettingsOption::WindowScale => {
+                let index = WINDOW_SCALES
+                    .iter()
+                    .position(|scale| *scale == self.window_scale)
+                    .map_or(0, |index| (index + 1) % WINDOW_SCALES.len());
+                self.window_scale = WINDOW_SCALES[index];
+            }
+            SettingsOption::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingsOption::MasterVolume => {
+                self.master_volume = cycle(self.master_volume, VOLUME_STEP, 1.)
+            }
+            SettingsOption::MusicVolume => {
+                self.music_volume = cycle(self.music_volume, VOLUME_STEP, 1.)
+            }
+            SettingsOption::SfxVolume => self.sfx_volume = cycle(self.sfx_volume, VOLUME_STEP, 1.),
+            SettingsOption::ScreenShake => {
+                self.screen_shake = cycle(self.screen_shake, SCREEN_SHAKE_STEP, 1.)
+            }
+            SettingsOption::Hitstop => self.hitstop = cycle(self.hitstop, HITSTOP_STEP, 1.),
+            SettingsOption::Hitboxes => self.show_hitbox = !self.show_hitbox,
+            SettingsOption::Particles => self.particles = !self.particles,
+        }
+    }
+
+    pub fn label(&self, option: SettingsOption) -> String {
+        match option {
+            SettingsOption::VSync => format!("VSync: {}", on_off(self.vsync)),
+            SettingsOption::WindowScale => format!("Window Scale: {}x", self.window_scale),
+            SettingsOption::Fullscreen => format!("Fullscreen: {}", on_off(self.fullscreen)),
+            SettingsOption::MasterVolume => {
+                format!("Master Volume: {:.0}%", self.master_volume * 100.)
+            }
+            SettingsOption::MusicVolume => {
+                format!("Music Volume: {:.0}%", self.music_volume * 100.)
+            }
+            SettingsOption::SfxVolume => format!("SFX Volume: {:.0}%", self.sfx_volume * 100.),
+            SettingsOption::ScreenShake => {
+                format!("Screen Shake: {:.0}%", self.screen_shake * 100.)
+            }
+            SettingsOption::Hitstop => format!("Hitstop: {:.0}%", self.hitstop * 100.),
+            SettingsOption::Hitboxes => format!("Hitboxes: {}", on_off(self.show_hitbox)),
+            SettingsOption::Particles => format!("Particles: {}", on_off(self.particles)),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsMenu;
+
+pub fn setup_settings_menu(mut commands: Commands, settings: Res<Settings>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    align_content: AlignContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.),
+                    column_gap: Val::Px(6.),
+                    padding: UiRect::all(Val::Px(WINDOW_PADDING)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            SettingsMenu,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "Settings",
+                    TextStyle {
+                        font_size: 24.,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                        ..default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center)
+                .with_style(Style {
+                    width: Val::Percent(100.),
+                    ..default()
+                }),
+            );
+            for option in SettingsOption::ALL {
+                spawn_button(parent, &settings.label(option), MenuButton::Setting(option));
+            }
+            spawn_button(parent, "Back", MenuButton::CloseSettings);
+        });
+}
+
+pub fn cleanup_settings_menu(mut commands: Commands, query: Query<Entity, With<SettingsMenu>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn settings_menu(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut settings: ResMut<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    interaction_query: Query<(&Interaction, &MenuButton), (Changed<Interaction>, With<Button>)>,
+) {
+    let in_run = *state.get() == GameState::Running;
+    for (interaction, action) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match action {
+            MenuButton::Settings if in_run => next_pause_state.set(PauseState::Settings),
+            MenuButton::Settings => next_state.set(GameState::Settings),
+            MenuButton::CloseSettings if in_run => next_pause_state.set(PauseState::Paused),
+            MenuButton::CloseSettings => next_state.set(GameState::Menu),
+            MenuButton::Setting(option) => {
+                settings.cycle(*option);
+                settings.save();
+                if let Ok(mut window) = window_query.get_single_mut() {
+                    if matches!(
+                        option,
+                        SettingsOption::VSync
+                            | SettingsOption::WindowScale
+                            | SettingsOption::Fullscreen
+                    ) {
+                        settings.apply_window(&mut window);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn update_settings_labels(
+    settings: Res<Settings>,
+    button_query: Query<(&MenuButton, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (action, children) in button_query.iter() {
+        let MenuButton::Setting(option) = action else {
+            continue;
+        };
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = settings.label(*option);
+            }
+        }
+    }
+}
+
+pub fn update_hitbox_display(
+    settings: Res<Settings>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(&Handle<ColorMaterial>, Ref<Player>)>,
+) {
+    for (handle, player) in query.iter() {
+        if !settings.is_changed() && !player.is_added() {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(handle) {
+            let alpha = if settings.show_hitbox { 1. } else { 0. };
+            material.color = PLAYER_COLOR.with_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::menu::{spawn_button, MenuData};
+use crate::ron_loader::RonAsset;
+use crate::weapons::{BombKind, WeaponKind};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+#[derive(Asset, Clone, Debug, Deserialize, TypePath)]
+pub struct ShipDefinition {
+    pub id: String,
+    pub name: String,
+    pub sprite: String,
+    pub size: f32,
+    pub speed: f32,
+    pub focus_speed: f32,
+    pub hitbox_size: f32,
+    pub graze_size: f32,
+    pub fire_rate: f32,
+    pub shot_type: WeaponKind,
+    pub bomb_type: BombKind,
+}
+
+impl RonAsset for ShipDefinition {
+    const EXTENSIONS: &'static [&'static str] = &["ship.ron"];
+}
+
+impl Default for ShipDefinition {
+    fn default() -> Self {
+        Self {
+            id: "arrow".to_string(),
+            name: "Arrow".to_string(),
+            sprite: "ship.png".to_string(),
+            size: SHIP_SIZE.x,
+            speed: PLAYER_SPEED,
+            focus_speed: PLAYER_FOCUS_SPEED,
+            hitbox_size: PLAYER_SIZE.x,
+            graze_size: SHIP_SIZE.x,
+            fire_rate: PLAYER_FIRE_RATE,
+            shot_type: WeaponKind::Spread,
+            bomb_type: BombKind::Screen,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ShipStats {
+    pub speed: f32,
+    pub focus_speed: f32,
+    // Full side length of the square hitbox centred on the player.
+    pub hitbox_size: f32,
+    // Full side length of the graze box centred on the ship; curvy lasers
+    // widen it by their own width.
+    pub graze_size: f32,
+    pub bomb: BombKind,
+}
+
+impl From<&ShipDefinition> for ShipStats {
+    fn from(definition: &ShipDefinition) -> Self {
+        Self {
+            speed: definition.speed,
+            focus_speed: definition.focus_speed,
+            hitbox_size: definition.hitbox_size,
+            graze_size: definition.graze_size,
+            bomb: definition.bomb_type,
+        }
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct ShipDefinitions {
+    pub handles: Vec<Handle<ShipDefinition>>,
+}
+
+#[derive(Default, Resource)]
+pub struct SelectedShip {
+    pub definition: ShipDefinition,
+}
+
+pub fn load_ship_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ShipDefinitions {
+        handles: SHIP_DEFINITIONS
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    });
+}
+
+pub fn setup_ship_select(
+    mut commands: Commands,
+    ships: Res<ShipDefinitions>,
+    definitions: Res<Assets<ShipDefinition>>,
+) {
+    let text_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                padding: UiRect::all(Val::Px(WINDOW_PADDING)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "Select Ship",
+                    TextStyle {
+                        font_size: 40.,
+                        color: Color::rgb(0.5, 0.0, 0.0),
+                        ..default()
+                    },
+                ),
+                ColorText,
+            ));
+        })
+        .id();
+
+    let button_entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (index, handle) in ships.handles.iter().enumerate() {
+                let Some(definition) = definitions.get(handle) else {
+                    continue;
+                };
+                spawn_button(
+                    parent,
+                    &format!(
+                        "{}\n{:?} shot / {:?} bomb",
+                        definition.name, definition.shot_type, definition.bomb_type
+                    ),
+                    MenuButton::SelectShip(index),
+                );
+            }
+            spawn_button(parent, "Back", MenuButton::Back);
+        })
+        .id();
+
+    commands.insert_resource(MenuData {
+        button_entity,
+        text_entity,
+    });
+}
+
+pub fn cleanup_ship_select(mut commands: Commands, menu_data: Res<MenuData>) {
+    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.text_entity).despawn_recursive();
+}
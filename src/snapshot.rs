@@ -0,0 +1,360 @@
+use crate::combo::Combo;
+use crate::components::*;
+use crate::constants::*;
+use crate::enemies::{enemy_bundle, Boss, Hover};
+use crate::impact::Hitstop;
+use crate::items::spawn_item;
+use crate::lasers::{spawn_curvy_laser, spawn_enemy_laser, CurvyLaser, EnemyLaser};
+use crate::patterns::PatternScript;
+use crate::player::{spawn_bullet, BulletAssets, PlayerStock};
+use crate::stage::{StageDefinitions, StageDirector};
+use crate::storage::{data_path, load_ron, save_ron};
+use crate::weapons::{Missile, PlayerLaser, Weapon};
+use crate::Score;
+use bevy::{ecs::system::SystemState, prelude::*};
+use bevy_prng::ChaCha8Rng;
+use bevy_rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+const QUICKSAVE_FILE: &str = "quicksave.ron";
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EnemySnapshot {
+    pub position: Vec3,
+    pub color: [f32; 4],
+    pub size: f32,
+    pub enemy: Enemy,
+    pub hover: Option<Hover>,
+    pub pattern: Option<PatternScript>,
+    pub boss: bool,
+    pub grazed: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Snapshot {
+    pub score: i32,
+    pub stock: PlayerStock,
+    pub combo: Combo,
+    pub rng: GlobalEntropy<ChaCha8Rng>,
+    pub stage: usize,
+    pub stage_elapsed: f32,
+    pub next_event: usize,
+    pub ship: Vec3,
+    pub player: Vec3,
+    pub weapon: Weapon,
+    pub invulnerable: Option<Invulnerable>,
+    pub enemies: Vec<EnemySnapshot>,
+    pub bullets: Vec<(Vec3, Bullet)>,
+    pub items: Vec<(Vec3, Item)>,
+    pub lasers: Vec<(Vec3, EnemyLaser)>,
+    pub curvy_lasers: Vec<(CurvyLaser, bool)>,
+}
+
+type TransientQuery<'w, 's> = Query<
+    'w,
+    's,
+    Entity,
+    Or<(
+        With<Enemy>,
+        With<Bullet>,
+        With<Item>,
+        With<EnemyLaser>,
+        With<CurvyLaser>,
+        With<Missile>,
+        With<PlayerLaser>,
+    )>,
+>;
+
+type RestoreState<'w, 's> = (
+    Commands<'w, 's>,
+    Res<'w, AssetServer>,
+    Res<'w, BulletAssets>,
+    TransientQuery<'w, 's>,
+    Query<'w, 's, (&'static mut Transform, &'static mut Weapon), With<Ship>>,
+    Query<
+        'w,
+        's,
+        (Entity, &'static mut Transform, &'static mut Visibility),
+        (With<Player>, Without<Ship>),
+    >,
+);
+
+impl Snapshot {
+    pub fn capture(world: &mut World) -> Option<Self> {
+        let (ship, weapon) = world
+            .query_filtered::<(&Transform, &Weapon), With<Ship>>()
+            .get_single(world)
+            .map(|(transform, weapon)| (transform.translation, weapon.clone()))
+            .ok()?;
+        let (player, invulnerable) = world
+            .query_filtered::<(&Transform, Option<&Invulnerable>), With<Player>>()
+            .get_single(world)
+            .map(|(transform, invulnerable)| (transform.translation, invulnerable.cloned()))
+            .ok()?;
+
+        let enemies = world
+            .query::<(
+                &Transform,
+                &Sprite,
+                &Enemy,
+                Option<&Hover>,
+                Option<&PatternScript>,
+                Has<Boss>,
+                Has<Grazed>,
+            )>()
+            .iter(world)
+            .map(
+                |(transform, sprite, enemy, hover, pattern, boss, grazed)| EnemySnapshot {
+                    position: transform.translation,
+                    color: sprite.color.as_rgba_f32(),
+                    size: sprite.custom_size.map_or(0., |size| size.x),
+                    enemy: enemy.clone(),
+                    hover: hover.cloned(),
+                    pattern: pattern.cloned(),
+                    boss,
+                    grazed,
+                },
+            )
+            .collect();
+        let bullets = world
+            .query::<(&Transform, &Bullet)>()
+            .iter(world)
+            .map(|(transform, bullet)| (transform.translation, bullet.clone()))
+            .collect();
+        let items = world
+            .query::<(&Transform, &Item)>()
+            .iter(world)
+            .map(|(transform, item)| (transform.translation, item.clone()))
+            .collect();
+        let lasers = world
+            .query::<(&Transform, &EnemyLaser)>()
+            .iter(world)
+            .map(|(transform, laser)| (transform.translation, laser.clone()))
+            .collect();
+        let curvy_lasers = world
+            .query::<(&CurvyLaser, Has<Grazed>)>()
+            .iter(world)
+            .map(|(laser, grazed)| (laser.clone(), grazed))
+            .collect();
+
+        let director = world.resource::<StageDirector>();
+        Some(Self {
+            score: world.resource::<Score>().value,
+            stock: world.resource::<PlayerStock>().clone(),
+            combo: world.resource::<Combo>().clone(),
+            rng: world.resource::<GlobalEntropy<ChaCha8Rng>>().clone(),
+            stage: world
+                .resource::<StageDefinitions>()
+                .handles
+                .iter()
+                .position(|handle| *handle == director.stage)
+                .unwrap_or_default(),
+            stage_elapsed: director.elapsed,
+            next_event: director.next_event,
+            ship,
+            player,
+            weapon,
+            invulnerable,
+            enemies,
+            bullets,
+            items,
+            lasers,
+            curvy_lasers,
+        })
+    }
+
+    // Missiles and the player's laser are rebuilt from input within a few
+    // ticks, so they are cleared rather than stored.
+    pub fn restore(&self, world: &mut World) {
+        world.resource_mut::<Score>().value = self.score;
+        *world.resource_mut::<PlayerStock>() = self.stock.clone();
+        *world.resource_mut::<Combo>() = self.combo.clone();
+        *world.resource_mut::<GlobalEntropy<ChaCha8Rng>>() = self.rng.clone();
+        *world.resource_mut::<Hitstop>() = Hitstop::default();
+        let stage = world
+            .resource::<StageDefinitions>()
+            .handles
+            .get(self.stage)
+            .cloned()
+            .unwrap_or_default();
+        *world.resource_mut::<StageDirector>() = StageDirector {
+            stage,
+            elapsed: self.stage_elapsed,
+            next_event: self.next_event,
+            seek_target: None,
+        };
+
+        let mut state = SystemState::<RestoreState<'static, 'static>>::new(world);
+        let (
+            mut commands,
+            asset_server,
+            bullet_assets,
+            transient_query,
+            mut ship_query,
+            mut player_query,
+        ) = state.get_mut(world);
+
+        for entity in transient_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        for (mut transform, mut weapon) in ship_query.iter_mut() {
+            transform.translation = self.ship;
+            *weapon = self.weapon.clone();
+        }
+        for (entity, mut transform, mut visibility) in player_query.iter_mut() {
+            transform.translation = self.player;
+            match &self.invulnerable {
+                Some(invulnerable) => {
+                    commands.entity(entity).insert(invulnerable.clone());
+                }
+                None => {
+                    commands.entity(entity).remove::<Invulnerable>();
+                    *visibility = Visibility::Inherited;
+                }
+            }
+        }
+
+        for snapshot in &self.enemies {
+            let [r, g, b, a] = snapshot.color;
+            let mut enemy = commands.spawn((
+                enemy_bundle(
+                    &asset_server,
+                    Color::rgba(r, g, b, a),
+                    snapshot.size,
+                    snapshot.position.truncate(),
+                ),
+                snapshot.enemy.clone(),
+            ));
+            if let Some(hover) = &snapshot.hover {
+                enemy.insert(hover.clone());
+            }
+            if let Some(pattern) = &snapshot.pattern {
+                enemy.insert(pattern.clone());
+            }
+            if snapshot.boss {
+                enemy.insert(Boss);
+            }
+            if snapshot.grazed {
+                enemy.insert(Grazed);
+            }
+        }
+        for (position, bullet) in &self.bullets {
+            spawn_bullet(
+                &mut commands,
+                &bullet_assets,
+                *position,
+                bullet.velocity,
+                bullet.damage,
+            );
+        }
+        for (position, item) in &self.items {
+            let entity = spawn_item(&mut commands, item.kind, *position, item.velocity);
+            commands.entity(entity).insert(item.clone());
+        }
+        for (position, laser) in &self.lasers {
+            let entity = spawn_enemy_laser(&mut commands, laser.definition, position.truncate());
+            commands.entity(entity).insert(laser.clone());
+        }
+        for (laser, grazed) in &self.curvy_lasers {
+            let origin = laser.points.first().copied().unwrap_or_default();
+            let entity = spawn_curvy_laser(&mut commands, laser.definition, origin);
+            commands.entity(entity).insert(laser.clone());
+            if *grazed {
+                commands.entity(entity).insert(Grazed);
+            }
+        }
+
+        state.apply(world);
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct RewindBuffer {
+    pub snapshots: VecDeque<Snapshot>,
+    pub ticks: u32,
+    // Whether the rewind key is down, sampled in Update so that gameplay
+    // never reads the keyboard itself.
+    pub held: bool,
+    pub rewinding: bool,
+}
+
+#[derive(Default, Resource)]
+pub struct QuickSave {
+    pub snapshot: Option<Snapshot>,
+}
+
+pub fn not_rewinding(buffer: Res<RewindBuffer>) -> bool {
+    !buffer.rewinding
+}
+
+pub fn reset_rewind(mut buffer: ResMut<RewindBuffer>) {
+    *buffer = RewindBuffer::default();
+}
+
+pub fn record_rewind(world: &mut World) {
+    let mut buffer = world.resource_mut::<RewindBuffer>();
+    buffer.ticks += 1;
+    if buffer.ticks % REWIND_INTERVAL != 0 {
+        return;
+    }
+    let Some(snapshot) = Snapshot::capture(world) else {
+        return;
+    };
+    let mut buffer = world.resource_mut::<RewindBuffer>();
+    buffer.snapshots.push_back(snapshot);
+    if buffer.snapshots.len() > REWIND_SNAPSHOTS {
+        buffer.snapshots.pop_front();
+    }
+}
+
+// Holding the rewind key steps back one stored snapshot per tick. The oldest
+// snapshot is kept so that holding it past the start just stays there.
+pub fn rewind_input(keyboard_input: Res<Input<KeyCode>>, mut buffer: ResMut<RewindBuffer>) {
+    buffer.held = keyboard_input.pressed(KeyCode::R);
+}
+
+pub fn rewind(world: &mut World) {
+    let mut buffer = world.resource_mut::<RewindBuffer>();
+    buffer.rewinding = buffer.held;
+    if !buffer.rewinding {
+        return;
+    }
+    let snapshot = if buffer.snapshots.len() > 1 {
+        buffer.snapshots.pop_back()
+    } else {
+        buffer.snapshots.back().cloned()
+    };
+    if let Some(snapshot) = snapshot {
+        snapshot.restore(world);
+    }
+}
+
+pub fn quick_save(world: &mut World) {
+    let keyboard_input = world.resource::<Input<KeyCode>>();
+    let save = keyboard_input.just_pressed(KeyCode::F5);
+    let load = keyboard_input.just_pressed(KeyCode::F9);
+
+    if save {
+        if let Some(snapshot) = Snapshot::capture(world) {
+            save_ron(data_path(QUICKSAVE_FILE), &snapshot);
+            world.resource_mut::<QuickSave>().snapshot = Some(snapshot);
+            info!("quick saved");
+        }
+    }
+    if load {
+        let snapshot = world
+            .resource::<QuickSave>()
+            .snapshot
+            .clone()
+            .or_else(|| load_ron(data_path(QUICKSAVE_FILE)));
+        match snapshot {
+            Some(snapshot) => {
+                snapshot.restore(world);
+                world.resource_mut::<RewindBuffer>().snapshots.clear();
+                info!("quick loaded");
+            }
+            None => info!("no quick save to load"),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{GameState, RunFlags};
+use bevy::prelude::*;
+
+pub const PRACTICE_SPEEDS: [f32; 3] = [1., 0.75, 0.5];
+
+#[derive(Resource)]
+pub struct GameSpeed {
+    pub factor: f32,
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self { factor: 1. }
+    }
+}
+
+impl GameSpeed {
+    pub fn next(&mut self) {
+        let index = PRACTICE_SPEEDS
+            .iter()
+            .position(|&speed| speed == self.factor)
+            .map_or(0, |index| (index + 1) % PRACTICE_SPEEDS.len());
+        self.factor = PRACTICE_SPEEDS[index];
+    }
+
+    pub fn assisted(&self) -> bool {
+        self.factor < 1.
+    }
+
+    pub fn label(&self) -> String {
+        format!("Speed: {:.0}%", self.factor * 100.)
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct FrameAdvance {
+    pub enabled: bool,
+    pub step: bool,
+}
+
+// The fixed timestep itself never changes: slowing virtual time just runs
+// fewer 1/64s ticks per real second, so a slowed run replays identically.
+pub fn apply_game_speed(
+    speed: Res<GameSpeed>,
+    state: Res<State<GameState>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let factor = match state.get() {
+        GameState::Running => speed.factor,
+        _ => 1.,
+    };
+    if time.relative_speed() != factor {
+        time.set_relative_speed(factor);
+    }
+}
+
+pub fn frame_advance_ready(advance: Res<FrameAdvance>) -> bool {
+    !advance.enabled || advance.step
+}
+
+pub fn consume_frame_step(mut advance: ResMut<FrameAdvance>) {
+    advance.step = false;
+}
+
+pub fn frame_advance_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut advance: ResMut<FrameAdvance>,
+    mut flags: ResMut<RunFlags>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F6) {
+        advance.enabled = !advance.enabled;
+        advance.step = false;
+        info!(
+            "frame advance {}",
+            if advance.enabled { "on" } else { "off" }
+        );
+    }
+    if advance.enabled && keyboard_input.just_pressed(KeyCode::F7) {
+        advance.step = true;
+    }
+    // Stepping tick by tick helps more than any slowed speed does.
+    if advance.enabled && *state.get() == GameState::Running && !flags.assisted {
+        flags.assisted = true;
+    }
+}
@@ -0,0 +1,206 @@
+use crate::background::BackgroundScroll;
+use crate::constants::*;
+use crate::enemies::{spawn_enemy_archetype, EnemyArchetype};
+use crate::lasers::{spawn_enemy_laser, LaserDefinition};
+use crate::patterns::PatternStep;
+use crate::playfield::Playfield;
+use crate::practice::PracticeConfig;
+use crate::ron_loader::RonAsset;
+use crate::{Difficulty, GameMode};
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+pub enum StageAction {
+    Spawn {
+        archetype: EnemyArchetype,
+        x: f32,
+        #[serde(default)]
+        pattern: Vec<PatternStep>,
+    },
+    Laser(LaserDefinition),
+    ScrollSpeed {
+        speed: f32,
+        #[serde(default)]
+        duration: f32,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StageEvent {
+    pub time: f32,
+    pub action: StageAction,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StageSection {
+    pub name: String,
+    pub time: f32,
+}
+
+#[derive(Asset, Clone, Debug, Deserialize, TypePath)]
+pub struct StageDefinition {
+    pub name: String,
+    #[serde(default = "default_scroll_speed")]
+    pub scroll_speed: f32,
+    #[serde(default)]
+    pub sections: Vec<StageSection>,
+    pub events: Vec<StageEvent>,
+}
+
+fn default_scroll_speed() -> f32 {
+    BACKGROUND_SCROLL_SPEED
+}
+
+impl RonAsset for StageDefinition {
+    const EXTENSIONS: &'static [&'static str] = &["stage.ron"];
+}
+
+#[derive(Default, Resource)]
+pub struct StageDefinitions {
+    pub handles: Vec<Handle<StageDefinition>>,
+}
+
+impl StageDefinitions {
+    pub fn get<'a>(
+        &self,
+        index: usize,
+        stages: &'a Assets<StageDefinition>,
+    ) -> Option<&'a StageDefinition> {
+        self.handles
+            .get(index)
+            .and_then(|handle| stages.get(handle))
+    }
+}
+
+#[derive(Default, Resource)]
+pub struct StageDirector {
+    pub stage: Handle<StageDefinition>,
+    pub elapsed: f32,
+    pub next_event: usize,
+    pub seek_target: Option<f32>,
+}
+
+impl StageDirector {
+    // Seeking restarts the timeline and lets fast_forward_stage replay it up
+    // to the target on the next tick, so everything spawned before that point
+    // is already in place.
+    pub fn seek(&mut self, time: f32) {
+        self.elapsed = 0.;
+        self.next_event = 0;
+        self.seek_target = (time > 0.).then_some(time);
+    }
+}
+
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StageFastForward;
+
+pub fn load_stage_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(StageDefinitions {
+        handles: STAGE_DEFINITIONS
+            .iter()
+            .map(|path| asset_server.load(*path))
+            .collect(),
+    });
+}
+
+pub fn reset_stage(
+    mut director: ResMut<StageDirector>,
+    stages: Res<StageDefinitions>,
+    definitions: Res<Assets<StageDefinition>>,
+    mode: Res<GameMode>,
+    practice: Res<PracticeConfig>,
+) {
+    let index = match *mode {
+        GameMode::Practice => practice.stage,
+        _ => 0,
+    };
+    *director = StageDirector {
+        stage: stages.handles.get(index).cloned().unwrap_or_default(),
+        ..default()
+    };
+    if *mode == GameMode::Practice {
+        if let Some(section) = stages
+            .get(index, &definitions)
+            .and_then(|stage| stage.sections.get(practice.section))
+        {
+            director.seek(section.time);
+        }
+    }
+}
+
+// Ticks the stage systems at the fixed step until the director reaches its
+// seek target. Enemies, patterns and lasers end up where they would be had
+// the stage played from the start without the player shooting anything.
+pub fn fast_forward_stage(world: &mut World) {
+    let director = world.resource::<StageDirector>();
+    let Some(target) = director.seek_target else {
+        return;
+    };
+    let step = world.resource::<Time>().delta_seconds();
+    let loaded = world
+        .resource::<Assets<StageDefinition>>()
+        .get(&director.stage)
+        .is_some();
+    if !loaded || step <= 0. {
+        return;
+    }
+
+    world.resource_mut::<StageDirector>().seek_target = None;
+    while world.resource::<StageDirector>().elapsed + step / 2. < target {
+        world.run_schedule(StageFastForward);
+    }
+    let mut scroll = world.resource_mut::<BackgroundScroll>();
+    let speed = scroll.target;
+    scroll.set(speed);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn run_stage(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut director: ResMut<StageDirector>,
+    stages: Res<Assets<StageDefinition>>,
+    asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    playfield: Res<Playfield>,
+    mut scroll: ResMut<BackgroundScroll>,
+) {
+    let Some(stage) = stages.get(&director.stage) else {
+        return;
+    };
+
+    director.elapsed += time.delta_seconds();
+    while let Some(event) = stage.events.get(director.next_event) {
+        if event.time > director.elapsed {
+            break;
+        }
+        match &event.action {
+            StageAction::Spawn {
+                archetype,
+                x,
+                pattern,
+            } => {
+                spawn_enemy_archetype(
+                    &mut commands,
+                    &asset_server,
+                    *archetype,
+                    Vec2::new(*x, playfield.top() - ENEMY_SPAWN_OFFSET),
+                    difficulty.enemy_speed_scale(),
+                    pattern.clone(),
+                );
+            }
+            StageAction::Laser(definition) => {
+                spawn_enemy_laser(
+                    &mut commands,
+                    *definition,
+                    Vec2::new(definition.x, definition.y),
+                );
+            }
+            StageAction::ScrollSpeed { speed, duration } => {
+                scroll.change_to(*speed, *duration);
+            }
+        }
+        director.next_event += 1;
+    }
+}
@@ -0,0 +1,54 @@
+use crate::events::*;
+use crate::input::{Actions, PlayerInput};
+use bevy::prelude::*;
+
+#[derive(Default, Resource)]
+pub struct RunStats {
+    pub time_survived: f32,
+    pub enemies_killed: u32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+    pub grazes: u32,
+    pub focus_time: f32,
+    pub max_combo: u32,
+}
+
+impl RunStats {
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.;
+        }
+        self.shots_hit as f32 / self.shots_fired as f32 * 100.
+    }
+
+    pub fn focus_percent(&self) -> f32 {
+        if self.time_survived <= 0. {
+            return 0.;
+        }
+        self.focus_time / self.time_survived * 100.
+    }
+}
+
+pub fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+pub fn track_time(time: Res<Time>, input: Res<PlayerInput>, mut stats: ResMut<RunStats>) {
+    stats.time_survived += time.delta_seconds();
+    if input.pressed(Actions::FOCUS) {
+        stats.focus_time += time.delta_seconds();
+    }
+}
+
+pub fn track_events(
+    mut stats: ResMut<RunStats>,
+    mut shots: EventReader<ShotFired>,
+    mut hits: EventReader<BulletHit>,
+    mut kills: EventReader<EnemyDestroyed>,
+    mut grazes: EventReader<Graze>,
+) {
+    stats.shots_fired += shots.read().count() as u32;
+    stats.shots_hit += hits.read().count() as u32;
+    stats.enemies_killed += kills.read().count() as u32;
+    stats.grazes += grazes.read().count() as u32;
+}
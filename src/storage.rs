@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+use directories::ProjectDirs;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "bevy_dodge")
+}
+
+pub fn data_path(file_name: &str) -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.data_dir().join(file_name))
+}
+
+pub fn config_path(file_name: &str) -> Option<PathBuf> {
+    project_dirs().map(|dirs| dirs.config_dir().join(file_name))
+}
+
+pub fn load_ron<T: DeserializeOwned>(path: Option<PathBuf>) -> Option<T> {
+    let path = path?;
+    let contents = fs::read_to_string(&path).ok()?;
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!("Could not parse {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+pub fn save_ron<T: Serialize>(path: Option<PathBuf>, value: &T) {
+    let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(err) => {
+            warn!("Could not serialize data: {}", err);
+            return;
+        }
+    };
+    save_bytes(path, contents.as_bytes());
+}
+
+pub fn save_bytes(path: Option<PathBuf>, contents: &[u8]) {
+    let Some(path) = path else {
+        warn!("No data directory available, not saving");
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        if let Err(err) = fs::create_dir_all(parent) {
+            warn!("Could not create {}: {}", parent.display(), err);
+            return;
+        }
+    }
+    if let Err(err) = fs::write(&path, contents) {
+        warn!("Could not write {}: {}", path.display(), err);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::components::*;
+use crate::constants::*;
+use crate::events::*;
+use crate::input::{Actions, PlayerInput};
+use bevy::{prelude::*, sprite::collide_aabb::collide, sprite::Anchor};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum WeaponKind {
+    #[default]
+    Spread,
+    Needle,
+    Homing,
+    Laser,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BombKind {
+    #[default]
+    Screen,
+    Barrier,
+}
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub fire_rate: f32,
+    pub cooldown: f32,
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind, fire_rate: f32) -> Self {
+        Self {
+            kind,
+            fire_rate,
+            cooldown: 0.,
+        }
+    }
+}
+
+pub struct Shot {
+    pub offset: Vec2,
+    pub velocity: Vec2,
+    pub damage: i32,
+}
+
+pub fn shot_pattern(kind: WeaponKind, power_level: u32, focused: bool) -> Vec<Shot> {
+    let count = 1 + power_level as i32;
+    let center = (count - 1) as f32 / 2.;
+    let damage = if focused {
+        FOCUSED_SHOT_DAMAGE
+    } else {
+        SHOT_DAMAGE
+    };
+
+    (0..count)
+        .map(|index| {
+            let step = index as f32 - center;
+            match kind {
+                WeaponKind::Spread => {
+                    let spread = if focused {
+                        FOCUSED_SPREAD_ANGLE
+                    } else {
+                        SPREAD_ANGLE
+                    };
+                    let angle = (step * spread).to_radians();
+                    Shot {
+                        offset: Vec2::new(0., 20.),
+                        velocity: Vec2::new(-angle.sin(), angle.cos()) * BULLET_SPEED,
+                        damage,
+                    }
+                }
+                WeaponKind::Homing => {
+                    let angle = (step * SPREAD_ANGLE * 2.).to_radians();
+                    Shot {
+                        offset: Vec2::new(step * NEEDLE_SPACING, 10.),
+                        velocity: Vec2::new(-angle.sin(), angle.cos()) * MISSILE_SPEED,
+                        damage: MISSILE_DAMAGE,
+                    }
+                }
+                WeaponKind::Needle | WeaponKind::Laser => {
+                    let spacing = if focused {
+                        FOCUSED_NEEDLE_SPACING
+                    } else {
+                        NEEDLE_SPACING
+                    };
+                    Shot {
+                        offset: Vec2::new(step * spacing, 20.),
+                        velocity: Vec2::new(0., BULLET_SPEED),
+                        damage,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+#[derive(Component)]
+pub struct Missile {
+    pub velocity: Vec2,
+    pub damage: i32,
+    pub target: Option<Entity>,
+    pub lifetime: f32,
+}
+
+#[derive(Component)]
+pub struct PlayerLaser {
+    pub tick: f32,
+}
+
+pub fn spawn_missile(commands: &mut Commands, position: Vec3, velocity: Vec2, damage: i32) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: MISSILE_COLOR,
+                custom_size: Some(MISSILE_SIZE),
+                ..default()
+            },
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        Missile {
+            velocity,
+            damage,
+            target: None,
+            lifetime: MISSILE_LIFETIME,
+        },
+    ));
+}
+
+pub fn steer_missiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    enemy_query: Query<(Entity, &Transform), (With<Enemy>, Without<Missile>)>,
+    mut missile_query: Query<(Entity, &mut Transform, &mut Missile)>,
+) {
+    for (entity, mut transform, mut missile) in missile_query.iter_mut() {
+        missile.lifetime -= time.delta_seconds();
+        if missile.lifetime <= 0. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        if missile
+            .target
+            .map_or(true, |target| !enemy_query.contains(target))
+        {
+            missile.target = enemy_query
+                .iter()
+                .min_by(|(_, a), (_, b)| {
+                    let a = a.translation.truncate().distance_squared(position);
+                    let b = b.translation.truncate().distance_squared(position);
+                    a.total_cmp(&b)
+                })
+                .map(|(enemy, _)| enemy);
+        }
+
+        if let Some((_, target)) = missile
+            .target
+            .and_then(|target| enemy_query.get(target).ok())
+        {
+            let desired = target.translation.truncate() - position;
+            if desired.length_squared() <= f32::EPSILON {
+                continue;
+            }
+            let angle = missile.velocity.angle_between(desired);
+            let max_turn = MISSILE_TURN_RATE * time.delta_seconds();
+            let turn = angle.clamp(-max_turn, max_turn);
+            missile.velocity = Vec2::from_angle(turn).rotate(missile.velocity);
+        }
+
+        transform.translation += (missile.velocity * time.delta_seconds()).extend(0.);
+        transform.rotation = Quat::from_rotation_z(Vec2::Y.angle_between(missile.velocity));
+    }
+}
+
+pub fn collide_missiles(
+    mut commands: Commands,
+    missile_query: Query<(Entity, &Transform, &Missile)>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut bullet_hit: EventWriter<BulletHit>,
+    mut damage_enemy: EventWriter<DamageEnemy>,
+) {
+    for (missile_entity, missile_transform, missile) in missile_query.iter() {
+        for (enemy_entity, enemy_transform) in enemy_query.iter() {
+            let collision = collide(
+                missile_transform.translation, // pos a
+                MISSILE_SIZE,                  // radius a
+                enemy_transform.translation,   // pos b
+                ENEMY_SHOT_HITBOX,             // radius b
+            );
+            if collision.is_some() {
+                commands.entity(missile_entity).despawn();
+                bullet_hit.send(BulletHit);
+                damage_enemy.send(DamageEnemy {
+                    target: enemy_entity,
+                    amount: missile.damage,
+                });
+                break;
+            }
+        }
+    }
+}
+
+pub fn ray_aabb(origin: Vec2, direction: Vec2, center: Vec2, half_size: Vec2) -> Option<f32> {
+    let min = center - half_size;
+    let max = center + half_size;
+    let mut near = f32::NEG_INFINITY;
+    let mut far = f32::INFINITY;
+
+    for axis in 0..2 {
+        if direction[axis].abs() < f32::EPSILON {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+        let a = (min[axis] - origin[axis]) / direction[axis];
+        let b = (max[axis] - origin[axis]) / direction[axis];
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+
+    if near > far || far < 0. {
+        return None;
+    }
+    Some(near.max(0.))
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_laser(
+    mut commands: Commands,
+    time: Res<Time>,
+    input: Res<PlayerInput>,
+    ship_query: Query<(&Transform, &Weapon), With<Ship>>,
+    enemy_query: Query<(Entity, &Transform), With<Enemy>>,
+    mut laser_query: Query<
+        (Entity, &mut Transform, &mut Sprite, &mut PlayerLaser),
+        (Without<Ship>, Without<Enemy>),
+    >,
+    mut shot_fired: EventWriter<ShotFired>,
+    mut bullet_hit: EventWriter<BulletHit>,
+    mut damage_enemy: EventWriter<DamageEnemy>,
+) {
+    let firing = ship_query
+        .get_single()
+        .ok()
+        .filter(|(_, weapon)| weapon.kind == WeaponKind::Laser && input.pressed(Actions::SHOOT));
+    let Some((ship, _)) = firing else {
+        for (entity, _, _, _) in laser_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    };
+
+    let focused = input.pressed(Actions::FOCUS);
+    let (width, damage) = if focused {
+        (FOCUSED_LASER_WIDTH, FOCUSED_LASER_DAMAGE)
+    } else {
+        (LASER_WIDTH, LASER_DAMAGE)
+    };
+    let origin = ship.translation.truncate() + Vec2::new(0., 20.);
+    let hit = enemy_query
+        .iter()
+        .filter_map(|(entity, transform)| {
+            ray_aabb(
+                origin,
+                Vec2::Y,
+                transform.translation.truncate(),
+                ENEMY_SHOT_HITBOX / 2. + Vec2::new(width / 2., 0.),
+            )
+            .map(|distance| (entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+    let length = hit.map_or(LASER_RANGE, |(_, distance)| distance);
+
+    let Ok((_, mut transform, mut sprite, mut laser)) = laser_query.get_single_mut() else {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: LASER_COLOR,
+                    custom_size: Some(Vec2::new(width, length)),
+                    anchor: Anchor::BottomCenter,
+                    ..default()
+                },
+                transform: Transform::from_translation(origin.extend(0.5)),
+                ..default()
+            },
+            PlayerLaser { tick: 0. },
+        ));
+        return;
+    };
+
+    transform.translation = origin.extend(0.5);
+    sprite.custom_size = Some(Vec2::new(width, length));
+    laser.tick -= time.delta_seconds();
+    if laser.tick > 0. {
+        return;
+    }
+    laser.tick = LASER_TICK;
+    shot_fired.send(ShotFired);
+    if let Some((target, _)) = hit {
+        bullet_hit.send(BulletHit);
+        damage_enemy.send(DamageEnemy {
+            target,
+            amount: damage,
+        });
+    }
+}